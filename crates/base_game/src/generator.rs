@@ -0,0 +1,280 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_smud::prelude::*;
+use jam4::{
+  boid::WallAvoidance,
+  level::LevelInfo,
+  moveable::{CollisionResponse, MoveableBounds},
+  obstacle::{Motion, MovingObstacle},
+  shape::Shape,
+};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::sdf::{build_maze, build_track};
+
+/// seed shared by every run, the same depth always generates the same level
+pub const RUN_SEED: u64 = 0x6a61_6d34;
+
+const WIDTH: f32 = 4000.;
+const BORDER: f32 = 3000.;
+const TRACK_HEIGHT: f32 = 10000.;
+const MAZE_SCALE: f32 = WIDTH / 20.;
+const ARENA_HEIGHT: f32 = WIDTH / 2.;
+const BOIDS_PER_SPAWN_POINT: i32 = 20;
+/// rough cruising speed of the player, used to estimate the time goal
+const PLAYER_SPEED: f32 = 500.;
+
+/// spawn points of level7's maze, in maze units
+const MAZE_SPAWN_POINTS: [(f32, f32); 6] = [
+  (15., -6.0),
+  (-4.0, -14.0),
+  (-18.0, -11.0),
+  (-16.0, 4.0),
+  (3.0, 6.0),
+  (12.0, 6.0),
+];
+
+/// Part of a run, segments are stacked bottom to top and span the whole corridor width
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+  /// zig-zag from `build_track`, entered and left on the right, on the left when flipped
+  Track { flipped: bool },
+  /// boxes from `build_maze`, open along both ends
+  Maze { flipped: bool },
+  /// open room with circular pillars, `(center, radius)`
+  /// pillars can sway together, which keeps the gaps between them
+  Arena {
+    pillars: Vec<(Vec2, f32)>,
+    sway: Option<Motion>,
+  },
+}
+
+impl Segment {
+  fn random(rng: &mut ChaCha8Rng, depth: u32, prev: &Segment) -> Self {
+    let maze_weight = 1 + depth.min(4);
+    match rng.gen_range(0..6 + maze_weight) {
+      // tracks only line up with tracks of the same orientation
+      0..=2 => Segment::Track {
+        flipped: match prev {
+          Segment::Track { flipped } => *flipped,
+          _ => rng.gen_bool(0.5),
+        },
+      },
+      3..=5 => Segment::random_arena(rng, depth),
+      _ => Segment::Maze {
+        flipped: rng.gen_bool(0.5),
+      },
+    }
+  }
+
+  /// pillars sit on a 4x2 grid, jitter and radius leave at least 600 between them
+  fn random_arena(rng: &mut ChaCha8Rng, depth: u32) -> Self {
+    let cell = WIDTH / 2.;
+    let count = rng.gen_range(1..=(2 + depth as usize).min(8));
+    let mut cells: Vec<_> = (0..8).collect();
+    cells.shuffle(rng);
+    let pillars = cells[..count]
+      .iter()
+      .map(|i| {
+        let center = Vec2::new(cell * ((i % 4) as f32 - 1.5), cell * ((i / 4) as f32 - 0.5));
+        let jitter = Vec2::new(rng.gen_range(-300.0..300.0), rng.gen_range(-300.0..300.0));
+        (center + jitter, rng.gen_range(150.0..400.0))
+      })
+      .collect();
+    // starting from the middle, so the layout at the start is the one that gets validated
+    let sway = (depth > 2).then(|| Motion::Sine {
+      amplitude: Vec2::new(rng.gen_range(200.0..300.0), 0.0),
+      spin: 0.0,
+      period: rng.gen_range(3.0..6.0),
+      phase: 0.0,
+    });
+    Segment::Arena { pillars, sway }
+  }
+
+  pub fn half_height(&self) -> f32 {
+    match self {
+      Segment::Track { .. } => TRACK_HEIGHT,
+      Segment::Maze { .. } => MAZE_SCALE * 20.,
+      Segment::Arena { .. } => ARENA_HEIGHT,
+    }
+  }
+
+  /// walls, centered on the segment
+  pub fn shape(&self) -> Option<Shape> {
+    match self {
+      Segment::Track { flipped: false } => Some(build_track(WIDTH, TRACK_HEIGHT)),
+      Segment::Track { flipped: true } => Some(build_track(WIDTH, TRACK_HEIGHT).flip()),
+      Segment::Maze { flipped: false } => Some(build_maze(MAZE_SCALE)),
+      Segment::Maze { flipped: true } => Some(build_maze(MAZE_SCALE).flip()),
+      Segment::Arena { sway: Some(_), .. } => None,
+      Segment::Arena { pillars, .. } => pillars
+        .iter()
+        .map(|(center, radius)| Shape::Circle(*radius).translate(*center))
+        .reduce(Shape::union),
+    }
+  }
+
+  /// moving walls, centered on the segment
+  pub fn obstacles(&self) -> Vec<MovingObstacle> {
+    let Segment::Arena {
+      pillars,
+      sway: Some(motion),
+    } = self
+    else {
+      return Vec::new();
+    };
+    pillars
+      .iter()
+      .map(|(center, radius)| MovingObstacle {
+        shape: Shape::Circle(*radius),
+        position: *center,
+        motion: motion.clone(),
+      })
+      .collect()
+  }
+
+  fn spawn_points(&self, rng: &mut ChaCha8Rng) -> Vec<Vec2> {
+    match self {
+      Segment::Track { flipped: false } => vec![Vec2::new(-WIDTH / 4., 0.)],
+      Segment::Track { flipped: true } => vec![Vec2::new(WIDTH / 4., 0.)],
+      Segment::Maze { flipped } => {
+        let sign = if *flipped { -1. } else { 1. };
+        MAZE_SPAWN_POINTS
+          .choose_multiple(rng, 2)
+          .map(|(x, y)| Vec2::new(*x, *y) * MAZE_SCALE * sign)
+          .collect()
+      }
+      Segment::Arena { .. } => vec![Vec2::ZERO],
+    }
+  }
+
+  pub fn difficulty(&self) -> f32 {
+    match self {
+      Segment::Track { .. } => 1.0,
+      Segment::Maze { .. } => 2.0,
+      Segment::Arena { pillars, sway } => {
+        0.5 + 0.1 * pillars.len() as f32 + if sway.is_some() { 0.5 } else { 0.0 }
+      }
+    }
+  }
+
+  /// how much longer than a straight line the way through is
+  fn detour(&self) -> f32 {
+    match self {
+      Segment::Track { .. } => 1.5,
+      Segment::Maze { .. } => 2.0,
+      Segment::Arena { .. } => 1.0,
+    }
+  }
+}
+
+/// Segments of the run level at `depth`, bottom to top, starting with an empty arena
+/// runs get longer and have more mazes the deeper they go
+pub fn plan_run(seed: u64, depth: u32) -> Vec<Segment> {
+  plan(&mut run_rng(seed, depth), depth)
+}
+
+fn run_rng(seed: u64, depth: u32) -> ChaCha8Rng {
+  let mut rng = ChaCha8Rng::seed_from_u64(seed);
+  rng.set_stream(depth as u64);
+  rng
+}
+
+fn plan(rng: &mut ChaCha8Rng, depth: u32) -> Vec<Segment> {
+  let count = (3 + depth as usize / 2).min(10);
+  let mut segments = vec![Segment::Arena {
+    pillars: vec![],
+    sway: None,
+  }];
+  for _ in 0..count {
+    let next = Segment::random(rng, depth, &segments[segments.len() - 1]);
+    segments.push(next);
+  }
+  segments
+}
+
+pub fn build_level(asset_server: &AssetServer, seed: u64, depth: u32) -> LevelInfo {
+  let mut rng = run_rng(seed, depth);
+  let segments = plan(&mut rng, depth);
+
+  let w = WIDTH;
+  let h: f32 = segments.iter().map(Segment::half_height).sum();
+
+  let mut walls = Vec::new();
+  let mut obstacles = Vec::new();
+  let mut spawn_points = Vec::new();
+  let mut bottom = -h;
+  for segment in segments.iter() {
+    let center = Vec2::new(0., bottom + segment.half_height());
+    if let Some(shape) = segment.shape() {
+      walls.push(shape.translate(center));
+    }
+    obstacles.extend(segment.obstacles().into_iter().map(|o| MovingObstacle {
+      position: o.position + center,
+      ..o
+    }));
+    spawn_points.extend(
+      segment
+        .spawn_points(&mut rng)
+        .into_iter()
+        .map(|p| p + center),
+    );
+    bottom += segment.half_height() * 2.;
+  }
+
+  let fbounds = Vec4::new(0.0, h + (w - 1000.), w, w);
+  let outer = Shape::Box(Vec2::new(w + BORDER, h + BORDER));
+  let inner = Shape::Box(Vec2::new(w, h));
+  let shape = outer.subtract(inner.subtract(Shape::Union(walls)));
+
+  let finish_bounds = Shape::rect(fbounds.xy(), fbounds.zw());
+  let bounds = shape.subtract(finish_bounds.clone());
+  let fill_shader = asset_server.load("preload/terrain_fill.wgsl");
+
+  let s = SmudShape {
+    color: Color::BLACK,
+    sdf: asset_server.add(bounds.shader(&format!("smud::run{depth}"))),
+    frame: Frame::Quad((h + BORDER * 2.).max(50000.)),
+    fill: fill_shader.clone(),
+  };
+  let obstacle_sdfs = obstacles
+    .iter()
+    .enumerate()
+    .map(|(i, o)| {
+      let sdf = asset_server.add(o.shape.shader(&format!("smud::run{depth}_obstacle{i}")));
+      o.smud_shape(sdf, fill_shader.clone(), Color::BLACK)
+    })
+    .collect();
+
+  // harder runs ask for fewer shapes but give more time
+  let difficulty: f32 = segments.iter().map(Segment::difficulty).sum::<f32>() + depth as f32;
+  let boids = spawn_points.len() as f32 * BOIDS_PER_SPAWN_POINT as f32;
+  let rescue_ratio = (0.6 - 0.02 * difficulty).clamp(0.25, 0.6);
+  let distance: f32 = segments
+    .iter()
+    .map(|s| s.half_height() * 2. * s.detour())
+    .sum();
+  let seconds = distance / PLAYER_SPEED * (1. + 0.05 * difficulty);
+
+  LevelInfo {
+    bounds: MoveableBounds::from_sdf(bounds).with_obstacles(obstacles),
+    bake_bounds: None,
+    finish_bounds_box: MoveableBounds::from_sdf(finish_bounds),
+    finish_bounds: fbounds,
+    bounds_sdf: Some(s),
+    obstacle_sdfs,
+    name: format!("Run {depth}"),
+    next_level: None,
+    starting_point: Vec2::new(0., -h + ARENA_HEIGHT / 2.),
+    boids_per_spawn_point: BOIDS_PER_SPAWN_POINT,
+    spawn_points,
+    rescue_goal: Some(((boids * rescue_ratio) as u16).max(1)),
+    time_goal: Some(Duration::from_secs((seconds / 10.).ceil() as u64 * 10)),
+    wander: depth > 2,
+    wall_avoidance: WallAvoidance::Probes,
+    collision: CollisionResponse::Stop,
+    star_thresholds: None,
+  }
+}
@@ -1,87 +1,226 @@
 use bevy::prelude::*;
 use jam4::{
-  level::{LevelInfo, LevelRegistry},
-  GameModuleDescriptor, NativeGameModule,
+  level::{LevelId, LevelManager, LevelRegistry, PendingLevel},
+  GameModule, GameModuleDescriptor, ModuleInfo, NativeGameModule,
 };
 
-mod level1;
-mod level2;
-mod level3;
-mod level4;
-mod level5;
-mod level6;
-mod level7;
+pub mod generator;
 mod sdf;
 
+pub const MODULE_ID: &str = "base_game";
+
 pub fn get_module() -> GameModuleDescriptor {
-  GameModuleDescriptor::Native(NativeGameModule {
-    register_init,
-    register_setup,
-    register_update,
-  })
+  GameModuleDescriptor {
+    info: ModuleInfo {
+      id: MODULE_ID.to_owned(),
+      version: env!("CARGO_PKG_VERSION").to_owned(),
+      depends_on: Vec::new(),
+    },
+    module: GameModule::Native(NativeGameModule {
+      register_init,
+      register_setup,
+      register_update,
+    }),
+  }
 }
 
 fn register_init(sched: &mut Schedule) {
   sched.add_systems(on_init);
 }
 
-fn register_setup(_sched: &mut Schedule) {}
+fn register_setup(sched: &mut Schedule) {
+  sched.add_systems(extend_run);
+}
 
 fn register_update(_sched: &mut Schedule) {}
 
-pub fn on_init(mut lvl_registry: ResMut<LevelRegistry>, asset_server: Res<AssetServer>) {
-  let lvl_id1 = 1u8.into();
-  let lvl_id2 = 2u8.into();
-  let lvl_id3 = 3u8.into();
-  let lvl_id4 = 4u8.into();
-  let lvl_id5 = 5u8.into();
-  let lvl_id6 = 6u8.into();
-  let lvl_id7 = 7u8.into();
-
-  lvl_registry.levels.insert(
-    lvl_id1,
-    LevelInfo {
-      next_level: Some(lvl_id2),
-      ..level1::build_level(&asset_server)
-    },
-  );
-  lvl_registry.levels.insert(
-    lvl_id2,
-    LevelInfo {
-      next_level: Some(lvl_id3),
-      ..level2::build_level(&asset_server)
-    },
-  );
-  lvl_registry.levels.insert(
-    lvl_id3,
-    LevelInfo {
-      next_level: Some(lvl_id4),
-      ..level3::build_level(&asset_server)
-    },
-  );
-  lvl_registry.levels.insert(
-    lvl_id4,
-    LevelInfo {
-      next_level: Some(lvl_id5),
-      ..level4::build_level(&asset_server)
-    },
-  );
-  lvl_registry.levels.insert(
-    lvl_id5,
-    LevelInfo {
-      next_level: Some(lvl_id6),
-      ..level5::build_level(&asset_server)
-    },
-  );
-  lvl_registry.levels.insert(
-    lvl_id6,
-    LevelInfo {
-      next_level: Some(lvl_id7),
-      ..level6::build_level(&asset_server)
-    },
-  );
-  lvl_registry
-    .levels
-    .insert(lvl_id7, level7::build_level(&asset_server));
-  lvl_registry.start_level = Some(lvl_id1);
+/// Endless run levels are `base_game:run1`, `base_game:run2`...
+pub fn run_level_id(depth: u32) -> LevelId {
+  LevelId::new(MODULE_ID, &format!("run{depth}")).expect("run level ids are valid")
+}
+
+fn run_depth(id: &LevelId) -> Option<u32> {
+  if id.module() != MODULE_ID {
+    return None;
+  }
+  id.name().strip_prefix("run")?.parse().ok()
+}
+
+/// Generates the run level after the one being played, so the run never ends
+pub fn extend_run(
+  lvl_mgr: Res<LevelManager>,
+  mut lvl_registry: ResMut<LevelRegistry>,
+  asset_server: Res<AssetServer>,
+) {
+  let Some(current) = lvl_mgr.current_level.clone() else {
+    return;
+  };
+  let Some(depth) = run_depth(&current) else {
+    return;
+  };
+  let next = run_level_id(depth + 1);
+  if lvl_registry.find_level(next.as_str()).is_none() {
+    let level = generator::build_level(&asset_server, generator::RUN_SEED, depth + 1);
+    if let Err(e) = lvl_registry.register(next.clone(), level) {
+      error!("base game could not extend the run: {e}");
+      return;
+    }
+  }
+  if let Some(level) = lvl_registry.levels.get_mut(&current) {
+    level.next_level = Some(next);
+  }
+}
+
+/// Numbered levels are `assets/levels/level{n}.level.ron`, the run is generated
+const LEVEL_COUNT: u8 = 7;
+
+pub fn on_init(
+  mut cmd: Commands,
+  mut lvl_registry: ResMut<LevelRegistry>,
+  mut lvl_mgr: ResMut<LevelManager>,
+  asset_server: Res<AssetServer>,
+) {
+  for number in 1..=LEVEL_COUNT {
+    cmd.spawn(PendingLevel::load(
+      &asset_server,
+      &format!("levels/level{number}.level.ron"),
+    ));
+  }
+  let run = generator::build_level(&asset_server, generator::RUN_SEED, 1);
+  if let Err(e) = lvl_registry.register(run_level_id(1), run) {
+    error!("base game could not register a level: {e}");
+  }
+  lvl_registry.start_level = Some(LevelId::from(1));
+  // the run is open from the start, later run levels are unlocked by playing
+  lvl_mgr.unlocked.insert(run_level_id(1));
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use bevy::{asset::AssetPlugin, prelude::*};
+  use jam4::{
+    boid::BoidConfig,
+    level::{validate_level, LevelFile, LevelRegistry},
+    GameControlCommand, Jam4Extensions, ModManager, SimulationState,
+  };
+
+  use super::*;
+
+  const ASSETS: &str = "../../assets";
+
+  /// Registers level files straight from disk instead of waiting for the asset server to load
+  /// them in the background, shaders are left as placeholders
+  fn register_pending_levels(app: &mut App) {
+    let mut qry = app.world.query::<(Entity, &PendingLevel)>();
+    let pending: Vec<_> = qry
+      .iter(&app.world)
+      .map(|(e, pending)| {
+        let path = pending.0.path().expect("levels are loaded from files");
+        (e, path.to_string())
+      })
+      .collect();
+    for (e, path) in pending {
+      let contents = std::fs::read(format!("{ASSETS}/{path}")).unwrap();
+      let file = LevelFile::parse(&contents).unwrap();
+      let id = file.id.clone();
+      let obstacle_sdfs = vec![Handle::default(); file.obstacles.len()];
+      let level = file.into_level(default(), default(), obstacle_sdfs);
+      app.world.resource_mut::<LevelRegistry>().register(id, level).unwrap();
+      app.world.despawn(e);
+    }
+  }
+
+  /// app with every base game level registered, including the ones loaded from files
+  fn initialized_app() -> App {
+    let mut app = App::new();
+    app
+      .add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+          file_path: ASSETS.to_owned(),
+          ..default()
+        },
+      ))
+      .add_headless_jam_game(Duration::from_secs_f32(1. / 60.));
+    app.world.resource_mut::<ModManager>().register(get_module());
+    app.world.send_event(GameControlCommand::Initialize);
+
+    for _ in 0..10 {
+      app.update();
+      register_pending_levels(&mut app);
+      if app.world.resource::<State<SimulationState>>().get() == &SimulationState::Ready {
+        return app;
+      }
+    }
+    panic!(
+      "base game did not initialize: {:?}",
+      app.world.resource::<jam4::SimulationErrors>().0
+    );
+  }
+
+  #[test]
+  fn every_level_can_be_finished() {
+    let app = initialized_app();
+    let clearance = app.world.resource::<BoidConfig>().player_turning_radius();
+    let lvl_reg = app.world.resource::<LevelRegistry>();
+    assert!(lvl_reg.find_level("base_game:level3").is_some());
+
+    let problems: Vec<_> = lvl_reg
+      .ordered_levels()
+      .into_iter()
+      .filter_map(|id| {
+        validate_level(&lvl_reg.levels[&id], clearance)
+          .err()
+          .map(|problems| format!("{id}: {problems:?}"))
+      })
+      .collect();
+    assert!(problems.is_empty(), "{problems:#?}");
+  }
+
+  #[test]
+  fn level_bounds_match_hand_computed_distances() {
+    let app = initialized_app();
+    let lvl_reg = app.world.resource::<LevelRegistry>();
+    let cases = [
+      // level 3 is a corridor 500 wide on each side, walls end 3500 from the center
+      (3, Vec2::ZERO, 500.),
+      (3, Vec2::new(2000., 0.), -1500.),
+      // level 3 finish is cut out of the walls from y = 5000
+      (3, Vec2::new(2000., 8000.), 3000.),
+      // level 4 has a box of 500 in the middle and a ring of 500 around each circle center
+      (4, Vec2::ZERO, 500.),
+      (4, Vec2::new(0., -4000.), -3500.),
+      (4, Vec2::new(3750., -4000.), 250.),
+      // level 1 has walls from y = -10000 down to the outer edge at -13000
+      (1, Vec2::new(0., -12000.), -1000.),
+    ];
+    for (number, p, expected) in cases {
+      let actual = lvl_reg.levels[&LevelId::from(number)]
+        .bounds
+        .distance_to_edge(p);
+      assert!(
+        (expected - actual).abs() < 0.01,
+        "level {number} at {p}: expected {expected}, got {actual}"
+      );
+    }
+  }
+
+  #[test]
+  fn run_levels_can_be_finished() {
+    let app = initialized_app();
+    let clearance = app.world.resource::<BoidConfig>().player_turning_radius();
+    let asset_server = app.world.resource::<AssetServer>();
+
+    let problems: Vec<_> = (1..=16)
+      .filter_map(|depth| {
+        let level = generator::build_level(asset_server, generator::RUN_SEED, depth);
+        validate_level(&level, clearance)
+          .err()
+          .map(|problems| format!("run {depth}: {problems:?}"))
+      })
+      .collect();
+    assert!(problems.is_empty(), "{problems:#?}");
+  }
 }
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use jam4::{
+  boid::{Boid, BoidConfig, BoidIndex},
+  moveable::MoveableBounds,
+  shape::Shape,
+};
+
+struct Scene {
+  boids: Vec<(Entity, Vec2, Boid)>,
+  bconfig: BoidConfig,
+  bounds: MoveableBounds,
+  finish_bounds: MoveableBounds,
+}
+
+impl Scene {
+  /// boids laid out on a golden angle spiral so density is roughly uniform, first boid is the
+  /// player
+  fn new(count: usize) -> Self {
+    let boids = (0..count)
+      .map(|i| {
+        let angle = i as f32 * 2.399_963;
+        let position = Vec2::from_angle(angle) * 60.0 * (i as f32).sqrt();
+        let boid = Boid {
+          direction: Vec2::from_angle(angle * 3.0),
+          vision: if i == 0 { 800. } else { 400. },
+          personal_space: if i == 0 { 100. } else { 20. },
+          is_player: i == 0,
+          speed: 800.,
+          turning_speed: 20.,
+        };
+        (Entity::from_raw(i as u32), position, boid)
+      })
+      .collect();
+
+    Self {
+      boids,
+      bconfig: BoidConfig::new(default(), default(), default(), default()),
+      bounds: MoveableBounds::from_sdf(
+        Shape::Box(Vec2::splat(50000.)).subtract(Shape::Box(Vec2::splat(10000.))),
+      ),
+      finish_bounds: MoveableBounds::from_sdf(Shape::rect(
+        Vec2::new(0.0, 20000.),
+        Vec2::splat(1000.),
+      )),
+    }
+  }
+
+  fn brute_force(&self) -> Vec<(Vec2, f32)> {
+    self
+      .boids
+      .iter()
+      .map(|(_, pos, boid)| {
+        boid.calculate_forces(
+          self.boids.iter().map(|(_, p, b)| (*p, b)),
+          &self.bconfig,
+          *pos,
+          &self.bounds,
+          &self.finish_bounds,
+          false,
+        )
+      })
+      .collect()
+  }
+
+  fn indexed(&self, index: &mut BoidIndex) -> Vec<(Vec2, f32)> {
+    index.rebuild(self.boids.iter().map(|(e, p, b)| (*e, *p, b)));
+    self
+      .boids
+      .iter()
+      .map(|(_, pos, boid)| {
+        boid.calculate_forces(
+          index.neighbours(*pos).map(|e| {
+            let (_, p, b) = &self.boids[e.index() as usize];
+            (*p, b)
+          }),
+          &self.bconfig,
+          *pos,
+          &self.bounds,
+          &self.finish_bounds,
+          false,
+        )
+      })
+      .collect()
+  }
+
+  /// the index may only change how fast neighbours are found, not the forces
+  fn assert_parity(&self) {
+    let mut index = BoidIndex::default();
+    for ((f1, s1), (f2, s2)) in self.brute_force().into_iter().zip(self.indexed(&mut index)) {
+      assert!(
+        f1.abs_diff_eq(f2, 1e-3) && s1 == s2,
+        "indexed forces differ: brute force {f1:?} {s1}, indexed {f2:?} {s2}"
+      );
+    }
+  }
+}
+
+fn boid_forces(c: &mut Criterion) {
+  let mut group = c.benchmark_group("boid_forces");
+  group.sample_size(10);
+  for count in [1000, 2000, 4000] {
+    let scene = Scene::new(count);
+    scene.assert_parity();
+
+    group.bench_with_input(BenchmarkId::new("brute_force", count), &scene, |b, scene| {
+      b.iter(|| black_box(scene.brute_force()))
+    });
+    group.bench_with_input(BenchmarkId::new("indexed", count), &scene, |b, scene| {
+      let mut index = BoidIndex::default();
+      b.iter(|| black_box(scene.indexed(&mut index)))
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, boid_forces);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use jam4::{
+  moveable::{BakeSettings, MoveableBounds},
+  shape::Shape,
+};
+
+const HALF_SIZE: f32 = 10000.;
+
+/// walled square filled with a grid of `count` x `count` boxes, roughly as complex as a maze
+fn boxes(count: usize) -> Shape {
+  let step = HALF_SIZE * 2. / count as f32;
+  let walls = (0..count * count)
+    .map(|i| {
+      let cell = Vec2::new((i % count) as f32, (i / count) as f32) + 0.5;
+      Shape::Box(Vec2::splat(step / 4.)).translate(cell * step - HALF_SIZE)
+    })
+    .collect();
+  Shape::Box(Vec2::splat(HALF_SIZE * 1.5))
+    .subtract(Shape::Box(Vec2::splat(HALF_SIZE)).subtract(Shape::Union(walls)))
+}
+
+/// points spread over the level, a stand-in for where the boids are
+fn points() -> Vec<Vec2> {
+  (0..4096)
+    .map(|i| {
+      let u = Vec2::new((i as f32 * 0.754_877_7).fract(), (i as f32 * 0.569_840_3).fract());
+      (u * 2.0 - 1.0) * HALF_SIZE
+    })
+    .collect()
+}
+
+fn bounds_queries(c: &mut Criterion) {
+  let mut group = c.benchmark_group("bounds");
+  let points = points();
+  for count in [4, 8, 16] {
+    let analytic = MoveableBounds::from_sdf(boxes(count));
+    let baked = analytic.bake(&BakeSettings {
+      min: Vec2::splat(-HALF_SIZE * 1.5),
+      max: Vec2::splat(HALF_SIZE * 1.5),
+      cell_size: 50.,
+    });
+
+    for (name, bounds) in [("analytic", &analytic), ("baked", &baked)] {
+      group.bench_with_input(BenchmarkId::new(name, count * count), bounds, |b, bounds| {
+        b.iter(|| {
+          for p in points.iter() {
+            black_box((bounds.distance_to_edge(*p), bounds.edge_normal(*p)));
+          }
+        })
+      });
+    }
+  }
+  group.finish();
+}
+
+criterion_group!(benches, bounds_queries);
+criterion_main!(benches);
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use crate::moveable::MoveableBounds;
 
-use super::BoidConfig;
+use super::{BoidConfig, WallAvoidance};
 
 #[derive(Component)]
 pub struct TamedBoid;
@@ -18,15 +18,29 @@ pub struct Boid {
 }
 
 impl Boid {
+  /// Steering away from walls, at most unit length
   pub fn calculate_bounds_force(
     &self,
     bconfig: &BoidConfig,
     position: Vec2,
     bounds: &MoveableBounds,
     finish_bounds: &MoveableBounds,
+  ) -> Vec2 {
+    match bconfig.wall_avoidance {
+      WallAvoidance::Probes => self.probe_bounds_force(bconfig, position, bounds, finish_bounds),
+      WallAvoidance::Gradient => self.gradient_bounds_force(position, bounds, finish_bounds),
+    }
+  }
+
+  /// Hard turn to the side away from whichever probe hits a wall
+  fn probe_bounds_force(
+    &self,
+    bconfig: &BoidConfig,
+    position: Vec2,
+    bounds: &MoveableBounds,
+    finish_bounds: &MoveableBounds,
   ) -> Vec2 {
     let v = self.direction * self.vision;
-    let tx2 = position + v;
 
     let rayl = position + bconfig.lprobe.mul_vec2(v);
     let rayr = position + bconfig.rprobe.mul_vec2(v);
@@ -35,24 +49,42 @@ impl Boid {
     let colr = bounds.distance_to_edge(rayr).min(finish_bounds.distance_to_edge(rayr));
 
     if coll < 0.0 && coll < colr {
-      return bconfig.rforce.mul_vec2(self.direction);
+      return bconfig.rforce.mul_vec2(self.direction).normalize_or_zero();
     } else if colr < 0.0 {
-      return bconfig.lforce.mul_vec2(self.direction);
+      return bconfig.lforce.mul_vec2(self.direction).normalize_or_zero();
     }
 
     Vec2::ZERO
   }
 
+  /// Push along the normal of the closest wall, fading out quadratically at the edge of vision
+  /// so boids ease along corridors instead of bouncing between their walls
+  fn gradient_bounds_force(
+    &self,
+    position: Vec2,
+    bounds: &MoveableBounds,
+    finish_bounds: &MoveableBounds,
+  ) -> Vec2 {
+    let d = bounds.distance_to_edge(position);
+    let fd = finish_bounds.distance_to_edge(position);
+    let (distance, normal) = if fd < d {
+      (fd, finish_bounds.edge_normal(position))
+    } else {
+      (d, bounds.edge_normal(position))
+    };
+    let proximity = (1.0 - distance / self.vision).clamp(0.0, 1.0);
+    normal * proximity * proximity
+  }
+
   // TODO: clean up this mess
-  pub fn calculate_forces(
+  pub fn calculate_forces<'a>(
     &self,
-    qry: &Query<(Entity, &Transform, &mut Boid, Option<&TamedBoid>)>,
+    neighbours: impl Iterator<Item = (Vec2, &'a Boid)>,
     bconfig: &BoidConfig,
     position2d: Vec2,
     bounds: &MoveableBounds,
     finish_bounds: &MoveableBounds,
     is_tamed: bool,
-    _gizmos: &mut Gizmos,
   ) -> (Vec2, f32) {
     // don't calculate forces for player boid
     if self.is_player {
@@ -70,14 +102,12 @@ impl Boid {
     // gizmos.line_2d(position2d, min_dest, Color::BLUE);
     // gizmos.line_2d(position2d, max_dest, Color::RED);
 
-    // TODO: optimize
-    for (_, t_other, boid_other, _) in qry.iter() {
+    for (position2d_other, boid_other) in neighbours {
       let factor = if boid_other.is_player {
         bconfig.player_influence
       } else {
         1.0
       };
-      let position2d_other = t_other.translation.xy();
       let diff = position2d_other - position2d;
       let dist = diff.length();
       let maxpspace = self.personal_space.max(boid_other.personal_space);
@@ -117,7 +147,7 @@ impl Boid {
     }
 
     (
-      ((bounds_force.normalize_or_zero() * bconfig.boundary)
+      ((bounds_force * bconfig.boundary)
         + (separation_force.normalize_or_zero() * bconfig.repulsion)
         + (alignment_force.normalize_or_zero()
           * if is_tamed || bconfig.wander {
@@ -131,3 +161,56 @@ impl Boid {
     )
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shape::Shape;
+
+  /// corridor 200 wide along y, walls on both sides from x = ±100
+  fn corridor() -> (MoveableBounds, MoveableBounds) {
+    let walls = Shape::Box(Vec2::splat(5000.)).subtract(Shape::Box(Vec2::new(100., 5000.)));
+    let finish = Shape::rect(Vec2::new(0., 4000.), Vec2::splat(100.));
+    (
+      MoveableBounds::from_sdf(walls),
+      MoveableBounds::from_sdf(finish),
+    )
+  }
+
+  fn boid() -> Boid {
+    Boid {
+      direction: Vec2::Y,
+      vision: 80.,
+      personal_space: 20.,
+      is_player: false,
+      speed: 100.,
+      turning_speed: 4.,
+    }
+  }
+
+  #[test]
+  fn gradient_force_points_away_from_the_nearest_wall() {
+    let (bounds, finish) = corridor();
+    let boid = boid();
+    // 40 away from a wall with a vision of 80 is half way in, squared
+    let right = boid.gradient_bounds_force(Vec2::new(60., 0.), &bounds, &finish);
+    assert!(right.abs_diff_eq(Vec2::new(-0.25, 0.), 1e-3), "{right}");
+    let left = boid.gradient_bounds_force(Vec2::new(-60., 0.), &bounds, &finish);
+    assert!(left.abs_diff_eq(Vec2::new(0.25, 0.), 1e-3), "{left}");
+    // both walls are out of sight
+    let center = boid.gradient_bounds_force(Vec2::ZERO, &bounds, &finish);
+    assert_eq!(center, Vec2::ZERO);
+  }
+
+  #[test]
+  fn probe_force_turns_away_from_the_probe_in_a_wall() {
+    let (bounds, finish) = corridor();
+    let config = BoidConfig::default();
+    let boid = boid();
+    let force = |x: f32| boid.probe_bounds_force(&config, Vec2::new(x, 0.), &bounds, &finish);
+    // right probe ends up in the wall, turn left
+    assert!(force(60.).abs_diff_eq(Vec2::new(-1., 0.), 1e-5));
+    assert!(force(-60.).abs_diff_eq(Vec2::new(1., 0.), 1e-5));
+    assert_eq!(force(0.), Vec2::ZERO);
+  }
+}
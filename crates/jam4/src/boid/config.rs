@@ -1,5 +1,16 @@
 use bevy::prelude::*;
 use bevy_hanabi::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How boids steer clear of walls and the finish
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WallAvoidance {
+  /// turn away when one of the `lprobe`/`rprobe` rays ahead ends up in a wall
+  #[default]
+  Probes,
+  /// push away along the wall normal, harder the closer the wall is
+  Gradient,
+}
 
 #[derive(Resource)]
 pub struct BoidConfig {
@@ -17,6 +28,7 @@ pub struct BoidConfig {
   pub rprobe: Mat2,
   pub lforce: Mat2,
   pub rforce: Mat2,
+  pub wall_avoidance: WallAvoidance,
   pub show_forces: bool,
   pub show_direction: bool,
   pub show_personal_space: bool,
@@ -92,6 +104,17 @@ impl FromWorld for BoidConfig {
       (color_wild, color_tame, color_tamed_boosted)
     };
 
+    BoidConfig::new(effect, color_wild, color_tamed, color_tamed_boosted)
+  }
+}
+
+impl BoidConfig {
+  pub fn new(
+    cotrails: Handle<EffectAsset>,
+    color_wild: Handle<ColorMaterial>,
+    color_tamed: Handle<ColorMaterial>,
+    color_tamed_boosted: Handle<ColorMaterial>,
+  ) -> Self {
     BoidConfig {
       max_speed: 1000.,
       min_speed: 300.,
@@ -108,16 +131,22 @@ impl FromWorld for BoidConfig {
       rprobe: Mat2::from_angle(-45.0f32.to_radians()),
       lforce: Mat2::from_angle(90.0f32.to_radians()),
       rforce: Mat2::from_angle(-90.0f32.to_radians()),
+      wall_avoidance: WallAvoidance::Probes,
       show_forces: false,
       show_direction: false,
       show_personal_space: false,
       show_vision: false,
       show_bounds: true,
-      cotrails: effect,
+      cotrails,
       color_wild,
       color_tamed,
       color_tamed_boosted,
       wander: true,
     }
   }
+
+  /// Tightest circle the player can fly, boosting trades speed for turning
+  pub fn player_turning_radius(&self) -> f32 {
+    (self.max_speed / self.max_turn_speed).min(self.min_speed / self.min_turn_speed)
+  }
 }
@@ -1,21 +1,28 @@
 use crate::{
   level::{LevelManager, LevelRegistry},
   moveable::{CollidedWithBounds, Moveable, MoveableBounds},
-  Player, PlayerInfo,
+  scoring::LevelStats,
+  Player, PlayerInfo, SimulationFailed,
 };
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
 
 mod components;
 mod config;
+mod neighbours;
 
 pub use components::*;
 pub use config::*;
+pub use neighbours::*;
 
 pub fn despawn_collided_boids(
   mut cmd: Commands,
-  qry: Query<Entity, (With<Boid>, Added<CollidedWithBounds>)>,
+  qry: Query<(Entity, Has<TamedBoid>), (With<Boid>, Added<CollidedWithBounds>)>,
+  mut stats: ResMut<LevelStats>,
 ) {
-  for e in qry.iter() {
+  for (e, tamed) in qry.iter() {
+    if tamed {
+      stats.boids_lost += 1;
+    }
     cmd.entity(e).despawn_recursive();
   }
 }
@@ -30,14 +37,28 @@ pub fn calc_tamed_boids(
   qry_check: Query<Entity, (With<Boid>, Without<Player>, With<TamedBoid>)>,
   player: Res<PlayerInfo>,
   bconfig: Res<BoidConfig>,
+  index: Res<BoidIndex>,
 ) {
   let Ok((p_trans, p_boid)) = qry_player.get_single() else {
     return;
   };
-  for (e, transform, mut boid, mut color) in qry.iter_mut() {
+  let in_range: HashSet<Entity> = index
+    .within(p_trans.translation.xy(), p_boid.vision)
+    .filter(|e| {
+      qry.get(*e).is_ok_and(|(_, transform, _, _)| {
+        transform.translation.distance_squared(p_trans.translation)
+          <= p_boid.vision * p_boid.vision
+      })
+    })
+    .collect();
+
+  // only boids near the player or previously tamed boids can change state
+  for e in in_range.iter().copied().chain(qry_check.iter()) {
+    let Ok((_, _, mut boid, mut color)) = qry.get_mut(e) else {
+      continue;
+    };
     let prev_is_tamed: bool = qry_check.get(e).is_ok();
-    let is_tamed =
-      transform.translation.distance_squared(p_trans.translation) <= p_boid.vision * p_boid.vision;
+    let is_tamed = in_range.contains(&e);
     if is_tamed && !prev_is_tamed {
       if player.in_boost_mode {
         boid.turning_speed = bconfig.max_turn_speed;
@@ -83,22 +104,34 @@ pub fn calculate_boid_direction(
   time: Res<Time>,
   lvl_mgr: Res<LevelManager>,
   lvl_reg: Res<LevelRegistry>,
+  index: Res<BoidIndex>,
+  mut failed: EventWriter<SimulationFailed>,
 ) {
-  let level_id = lvl_mgr.current_level.unwrap();
-  let lvl = lvl_reg.get_level(&level_id);
+  let lvl = match lvl_mgr.current(&lvl_reg) {
+    Ok(lvl) => lvl,
+    Err(e) => {
+      failed.send(e.into());
+      return;
+    }
+  };
 
   let changes = qry
     .iter()
     .map(|(e, t1, boid, tamed)| {
       let pos = t1.translation.xy();
+      let neighbours = index.neighbours(pos).filter_map(|other| {
+        qry
+          .get(other)
+          .ok()
+          .map(|(_, t_other, boid_other, _)| (t_other.translation.xy(), boid_other))
+      });
       let (force, speed_change) = boid.calculate_forces(
-        &qry,
+        neighbours,
         &bconfig,
         pos,
         &bounds,
         &lvl.finish_bounds_box,
         tamed.is_some(),
-        &mut gizmos,
       );
 
       if bconfig.show_forces {
@@ -142,6 +175,15 @@ pub fn update_tamed_boids(
   }
 }
 
+/// Boids keep heading where the walls deflected them
+pub fn follow_deflections(mut qry: Query<(&Moveable, &mut Boid)>) {
+  for (mov, mut boid) in qry.iter_mut() {
+    if let Some(direction) = mov.velocity.xy().try_normalize() {
+      boid.direction = direction;
+    }
+  }
+}
+
 pub fn update_boid_velocity(mut qry: Query<(&mut Moveable, &mut Transform, &Boid)>) {
   for (mut mov, mut t, boid) in qry.iter_mut() {
     let normalized = if boid.direction == Vec2::ZERO {
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::Boid;
+
+/// Uniform grid of boids, rebuilt every tick
+/// cells are at least as big as the largest vision/personal space so a 3x3 block of cells
+/// contains every boid that can influence a boid in the center cell.
+/// player boids are not bucketed since they influence every boid regardless of distance
+#[derive(Resource, Default)]
+pub struct BoidIndex {
+  cell_size: f32,
+  radius: f32,
+  cells: HashMap<IVec2, Vec<Entity>>,
+  players: Vec<Entity>,
+}
+
+impl BoidIndex {
+  pub fn rebuild<'a>(&mut self, boids: impl IntoIterator<Item = (Entity, Vec2, &'a Boid)>) {
+    let boids: Vec<_> = boids.into_iter().collect();
+    self.cells.clear();
+    self.players.clear();
+    self.radius = boids
+      .iter()
+      .filter(|(_, _, b)| !b.is_player)
+      .map(|(_, _, b)| b.vision.max(b.personal_space))
+      .fold(0.0, f32::max);
+    self.cell_size = self.radius.max(1.0);
+
+    for (e, pos, boid) in boids {
+      if boid.is_player {
+        self.players.push(e);
+      } else {
+        let cell = self.cell(pos);
+        self.cells.entry(cell).or_default().push(e);
+      }
+    }
+  }
+
+  /// Every boid that could affect the flocking forces of a boid at `pos`
+  pub fn neighbours(&self, pos: Vec2) -> impl Iterator<Item = Entity> + '_ {
+    self.within(pos, self.radius).chain(self.players.iter().copied())
+  }
+
+  /// Non-player boids in the cells overlapping the circle, callers still need to check the
+  /// actual distance
+  pub fn within(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+    let min = self.cell(pos - Vec2::splat(radius));
+    let max = self.cell(pos + Vec2::splat(radius));
+    (min.x..=max.x)
+      .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+      .filter_map(|cell| self.cells.get(&cell))
+      .flatten()
+      .copied()
+  }
+
+  fn cell(&self, pos: Vec2) -> IVec2 {
+    (pos / self.cell_size).floor().as_ivec2()
+  }
+}
+
+pub fn update_boid_index(mut index: ResMut<BoidIndex>, qry: Query<(Entity, &Transform, &Boid)>) {
+  index.rebuild(qry.iter().map(|(e, t, b)| (e, t.translation.xy(), b)));
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::HashSet, f32::consts::TAU};
+
+  use rand::{Rng, SeedableRng};
+  use rand_chacha::ChaCha8Rng;
+
+  use super::*;
+  use crate::{boid::BoidConfig, moveable::MoveableBounds};
+
+  /// boids on and right next to cell borders, on both sides of the origin
+  fn boids() -> Vec<(Entity, Vec2, Boid)> {
+    let coords = [
+      -800., -400.001, -400., -399.999, -0.001, 0., 0.001, 399.999, 400., 400.001, 800.,
+    ];
+    let mut boids: Vec<_> = coords
+      .iter()
+      .flat_map(|x| coords.iter().map(move |y| Vec2::new(*x, *y)))
+      .enumerate()
+      .map(|(i, position)| {
+        let boid = Boid {
+          direction: Vec2::from_angle(i as f32),
+          vision: if i % 2 == 0 { 400. } else { 250. },
+          personal_space: 20.,
+          is_player: false,
+          speed: 800.,
+          turning_speed: 20.,
+        };
+        (Entity::from_raw(i as u32), position, boid)
+      })
+      .collect();
+    let player = Boid {
+      direction: Vec2::Y,
+      vision: 800.,
+      personal_space: 100.,
+      is_player: true,
+      speed: 1000.,
+      turning_speed: 5.,
+    };
+    boids.push((Entity::from_raw(boids.len() as u32), Vec2::new(-1234., 567.), player));
+    boids
+  }
+
+  fn index(boids: &[(Entity, Vec2, Boid)]) -> BoidIndex {
+    let mut index = BoidIndex::default();
+    index.rebuild(boids.iter().map(|(e, p, b)| (*e, *p, b)));
+    index
+  }
+
+  #[test]
+  fn index_finds_every_boid_in_range() {
+    let boids = boids();
+    let index = index(&boids);
+    for (e, position, boid) in boids.iter().filter(|(_, _, b)| !b.is_player) {
+      let found: HashSet<_> = index.neighbours(*position).collect();
+      for (other, other_position, other_boid) in boids.iter() {
+        let range = boid.vision.max(other_boid.vision).max(other_boid.personal_space);
+        if other_boid.is_player || position.distance(*other_position) < range {
+          assert!(found.contains(other), "{other:?} is missing from {e:?}'s neighbours");
+        }
+      }
+    }
+  }
+
+  /// seeded crowd of a few thousand boids with mixed vision and personal space
+  fn crowd(count: usize) -> Vec<(Entity, Vec2, Boid)> {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    (0..count)
+      .map(|i| {
+        let position = Vec2::new(rng.gen_range(-6000.0..6000.0), rng.gen_range(-6000.0..6000.0));
+        let boid = Boid {
+          direction: Vec2::from_angle(rng.gen_range(0.0..TAU)),
+          vision: if i == 0 { 800. } else { rng.gen_range(200.0..600.0) },
+          personal_space: rng.gen_range(20.0..100.0),
+          is_player: i == 0,
+          speed: 800.,
+          turning_speed: 20.,
+        };
+        (Entity::from_raw(i as u32), position, boid)
+      })
+      .collect()
+  }
+
+  fn assert_indexed_forces_match(boids: &[(Entity, Vec2, Boid)], epsilon: f32) {
+    let index = index(boids);
+    let bconfig = BoidConfig::new(default(), default(), default(), default());
+    let bounds = MoveableBounds::default();
+
+    for (e, position, boid) in boids.iter() {
+      let forces = |neighbours: Vec<(Vec2, &Boid)>| {
+        boid.calculate_forces(neighbours.into_iter(), &bconfig, *position, &bounds, &bounds, false)
+      };
+      let (f1, s1) = forces(boids.iter().map(|(_, p, b)| (*p, b)).collect());
+      let (f2, s2) = forces(
+        index
+          .neighbours(*position)
+          .map(|n| {
+            let (_, p, b) = &boids[n.index() as usize];
+            (*p, b)
+          })
+          .collect(),
+      );
+      assert!(
+        f1.abs_diff_eq(f2, epsilon) && s1 == s2,
+        "{e:?} differs: brute force {f1:?} {s1}, indexed {f2:?} {s2}"
+      );
+    }
+  }
+
+  #[test]
+  fn indexed_forces_match_brute_force() {
+    assert_indexed_forces_match(&boids(), 1e-4);
+  }
+
+  #[test]
+  fn indexed_forces_match_brute_force_in_a_crowd() {
+    // neighbours are summed in a different order, so allow for some rounding
+    assert_indexed_forces_match(&crowd(2500), 1e-3);
+  }
+}
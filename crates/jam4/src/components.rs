@@ -1,8 +1,19 @@
 use bevy::prelude::*;
-
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 #[derive(Component)]
 pub struct Initializing;
 
 #[derive(Component)]
-pub struct Simulation;
\ No newline at end of file
+pub struct Simulation;
+
+/// Source of all randomness in the simulation, reseeded on every level load
+#[derive(Resource)]
+pub struct SimulationRng(pub ChaCha8Rng);
+
+impl Default for SimulationRng {
+  fn default() -> Self {
+    Self(ChaCha8Rng::seed_from_u64(0))
+  }
+}
@@ -0,0 +1,350 @@
+use std::time::Duration;
+
+use bevy::{
+  asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState},
+  prelude::*,
+  utils::BoxedFuture,
+};
+use bevy_smud::{Frame, SmudShape};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+  boid::WallAvoidance,
+  moveable::{BakeSettings, CollisionResponse, MoveableBounds},
+  obstacle::MovingObstacle,
+  scoring::StarThresholds,
+  shape::Shape,
+  Initializing,
+};
+
+use super::{LevelId, LevelInfo, LevelRegistry};
+
+/// On-disk description of a level, see `assets/levels/*.level.ron`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelFile {
+  pub id: LevelId,
+  pub name: String,
+  pub bounds: Shape,
+  /// xy is the center of the finish box, zw its half extents
+  pub finish_bounds: Vec4,
+  pub starting_point: Vec2,
+  pub spawn_points: Vec<Vec2>,
+  pub boids_per_spawn_point: i32,
+  #[serde(default)]
+  pub rescue_goal: Option<u16>,
+  /// in seconds
+  #[serde(default)]
+  pub time_goal: Option<f32>,
+  #[serde(default)]
+  pub wander: bool,
+  #[serde(default)]
+  pub wall_avoidance: WallAvoidance,
+  #[serde(default)]
+  pub collision: CollisionResponse,
+  #[serde(default)]
+  pub next_level: Option<LevelId>,
+  #[serde(default)]
+  pub star_thresholds: Option<StarThresholds>,
+  #[serde(default)]
+  pub obstacles: Vec<MovingObstacle>,
+  /// sample the bounds into a grid on load, for levels with complex bounds
+  #[serde(default)]
+  pub bake_bounds: Option<BakeSettings>,
+  #[serde(default)]
+  pub terrain: TerrainFile,
+}
+
+/// Rendering options for the terrain, the shader itself is generated from `bounds`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TerrainFile {
+  #[serde(default = "TerrainFile::default_fill")]
+  pub fill: String,
+  #[serde(default = "TerrainFile::default_color")]
+  pub color: Color,
+  #[serde(default = "TerrainFile::default_frame")]
+  pub frame: f32,
+}
+
+impl Default for TerrainFile {
+  fn default() -> Self {
+    Self {
+      fill: Self::default_fill(),
+      color: Self::default_color(),
+      frame: Self::default_frame(),
+    }
+  }
+}
+
+impl TerrainFile {
+  fn default_fill() -> String {
+    "preload/terrain_fill.wgsl".to_owned()
+  }
+  fn default_color() -> Color {
+    Color::BLACK
+  }
+  fn default_frame() -> f32 {
+    50000.
+  }
+}
+
+impl LevelFile {
+  /// Reads and validates a `.level.ron` file, this is all `LevelLoader` does before building
+  /// the shaders
+  pub fn parse(bytes: &[u8]) -> Result<Self, LevelLoaderError> {
+    let file = ron::de::from_bytes::<Self>(bytes)?;
+    file.validate()?;
+    Ok(file)
+  }
+
+  pub fn validate(&self) -> Result<(), LevelLoaderError> {
+    if self.boids_per_spawn_point < 0 {
+      return Err(LevelLoaderError::Invalid(
+        "boids_per_spawn_point must not be negative".to_owned(),
+      ));
+    }
+    if self.finish_bounds.z <= 0.0 || self.finish_bounds.w <= 0.0 {
+      return Err(LevelLoaderError::Invalid(
+        "finish_bounds must have a positive size".to_owned(),
+      ));
+    }
+    if let Some(bake) = self.bake_bounds {
+      if !(bake.cell_size > 0.0 && bake.max.cmpgt(bake.min).all()) {
+        return Err(LevelLoaderError::Invalid(
+          "bake_bounds must have a positive cell size and max above min".to_owned(),
+        ));
+      }
+    }
+    if let Some(time_goal) = self.time_goal {
+      if !time_goal.is_finite() || time_goal <= 0.0 {
+        return Err(LevelLoaderError::Invalid(
+          "time_goal must be a positive number of seconds".to_owned(),
+        ));
+      }
+    }
+    for obstacle in self.obstacles.iter() {
+      obstacle.motion.validate().map_err(LevelLoaderError::Invalid)?;
+    }
+    Ok(())
+  }
+
+  pub fn finish_shape(&self) -> Shape {
+    Shape::rect(self.finish_bounds.xy(), self.finish_bounds.zw())
+  }
+
+  /// Collision bounds, the finish area is cut out of the terrain so it can be reached
+  pub fn bounds_shape(&self) -> Shape {
+    self.bounds.clone().subtract(self.finish_shape())
+  }
+
+  /// `sdf` is the terrain shader generated from `bounds_shape`,
+  /// `obstacle_sdfs` are generated from the shape of each obstacle
+  pub fn into_level(
+    self,
+    sdf: Handle<Shader>,
+    fill: Handle<Shader>,
+    obstacle_sdfs: Vec<Handle<Shader>>,
+  ) -> LevelInfo {
+    let bounds = self.bounds_shape();
+    let obstacle_sdfs = self
+      .obstacles
+      .iter()
+      .zip(obstacle_sdfs)
+      .map(|(obstacle, sdf)| obstacle.smud_shape(sdf, fill.clone(), self.terrain.color))
+      .collect();
+    LevelInfo {
+      bounds: MoveableBounds::from_sdf(bounds).with_obstacles(self.obstacles),
+      bake_bounds: self.bake_bounds,
+      finish_bounds_box: MoveableBounds::from_sdf(self.finish_shape()),
+      finish_bounds: self.finish_bounds,
+      bounds_sdf: Some(SmudShape {
+        color: self.terrain.color,
+        sdf,
+        frame: Frame::Quad(self.terrain.frame),
+        fill,
+      }),
+      obstacle_sdfs,
+      name: self.name,
+      starting_point: self.starting_point,
+      next_level: self.next_level,
+      spawn_points: self.spawn_points,
+      boids_per_spawn_point: self.boids_per_spawn_point,
+      rescue_goal: self.rescue_goal,
+      time_goal: self.time_goal.map(Duration::from_secs_f32),
+      wander: self.wander,
+      wall_avoidance: self.wall_avoidance,
+      collision: self.collision,
+      star_thresholds: self.star_thresholds,
+    }
+  }
+}
+
+#[derive(Asset, TypePath)]
+pub struct LevelAsset {
+  pub id: LevelId,
+  pub level: LevelInfo,
+}
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+  #[error("could not read level file: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("could not parse level file: {0}")]
+  Parse(#[from] ron::error::SpannedError),
+  #[error("invalid level: {0}")]
+  Invalid(String),
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+  type Asset = LevelAsset;
+  type Settings = ();
+  type Error = LevelLoaderError;
+
+  fn load<'a>(
+    &'a self,
+    reader: &'a mut Reader,
+    _settings: &'a Self::Settings,
+    load_context: &'a mut LoadContext,
+  ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+    Box::pin(async move {
+      let mut bytes = Vec::new();
+      reader.read_to_end(&mut bytes).await?;
+      let file = LevelFile::parse(&bytes)?;
+
+      let import_path = format!(
+        "smud::terrain_{}",
+        load_context
+          .path()
+          .to_string_lossy()
+          .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+      );
+      let sdf = load_context.add_labeled_asset(
+        "terrain".to_owned(),
+        file.bounds_shape().shader(&import_path),
+      );
+      let obstacle_sdfs = file
+        .obstacles
+        .iter()
+        .enumerate()
+        .map(|(i, obstacle)| {
+          load_context.add_labeled_asset(
+            format!("obstacle{i}"),
+            obstacle.shape.shader(&format!("{import_path}_obstacle{i}")),
+          )
+        })
+        .collect();
+      let fill = load_context.load(&file.terrain.fill);
+
+      Ok(LevelAsset {
+        id: file.id.clone(),
+        level: file.into_level(sdf, fill, obstacle_sdfs),
+      })
+    })
+  }
+
+  fn extensions(&self) -> &[&str] {
+    &["level.ron"]
+  }
+}
+
+/// Level file that should be added to the registry once loaded
+/// spawn this during `ModInit`, initialization waits until all pending levels are processed
+#[derive(Component)]
+pub struct PendingLevel(pub Handle<LevelAsset>);
+
+impl PendingLevel {
+  pub fn load(asset_server: &AssetServer, path: &str) -> (Self, Initializing) {
+    (Self(asset_server.load(path.to_owned())), Initializing)
+  }
+}
+
+pub fn register_loaded_levels(
+  mut cmd: Commands,
+  qry: Query<(Entity, &PendingLevel)>,
+  asset_server: Res<AssetServer>,
+  mut levels: ResMut<Assets<LevelAsset>>,
+  mut lvl_reg: ResMut<LevelRegistry>,
+) {
+  for (e, pending) in qry.iter() {
+    match asset_server.load_state(pending.0.id()) {
+      LoadState::Loaded => {
+        if let Some(asset) = levels.remove(pending.0.id()) {
+          if let Err(e) = lvl_reg.register(asset.id, asset.level) {
+            error!("level {:?} was not registered: {e}", pending.0.path());
+          }
+        }
+        cmd.entity(e).despawn();
+      }
+      LoadState::Failed => {
+        // the loader error has already been logged by the asset server
+        error!(
+          "level {:?} failed to load and will not be registered",
+          pending.0.path()
+        );
+        cmd.entity(e).despawn();
+      }
+      _ => {}
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LEVEL: &str = r#"(
+    id: "test:level",
+    name: "Test",
+    bounds: Subtract(Box((3000.0, 3000.0)), Box((2000.0, 2000.0))),
+    finish_bounds: (0.0, 2500.0, 500.0, 500.0),
+    starting_point: (0.0, -1500.0),
+    spawn_points: [(0.0, 0.0)],
+    boids_per_spawn_point: 20,
+    time_goal: Some(60.0),
+  )"#;
+
+  #[test]
+  fn level_file_parses() {
+    let file = LevelFile::parse(LEVEL.as_bytes()).unwrap();
+    assert_eq!(file.id.as_str(), "test:level");
+    assert_eq!(file.spawn_points, vec![Vec2::ZERO]);
+    assert_eq!(file.wall_avoidance, WallAvoidance::Probes);
+  }
+
+  #[test]
+  fn malformed_ron_is_a_parse_error() {
+    for bytes in [&LEVEL[..LEVEL.len() / 2], "", "(id: 5)"] {
+      assert!(matches!(
+        LevelFile::parse(bytes.as_bytes()),
+        Err(LevelLoaderError::Parse(_))
+      ));
+    }
+    // an id without a module is rejected while parsing
+    let bad_id = LEVEL.replace("test:level", "level");
+    assert!(matches!(
+      LevelFile::parse(bad_id.as_bytes()),
+      Err(LevelLoaderError::Parse(_))
+    ));
+  }
+
+  #[test]
+  fn invalid_level_is_rejected() {
+    let invalid = [
+      LEVEL.replace("boids_per_spawn_point: 20", "boids_per_spawn_point: -1"),
+      LEVEL.replace("(0.0, 2500.0, 500.0, 500.0)", "(0.0, 2500.0, 0.0, 500.0)"),
+      LEVEL.replace("Some(60.0)", "Some(-1.0)"),
+    ];
+    for level in invalid {
+      assert!(
+        matches!(
+          LevelFile::parse(level.as_bytes()),
+          Err(LevelLoaderError::Invalid(_))
+        ),
+        "{level}"
+      );
+    }
+  }
+}
@@ -1,18 +1,28 @@
+use std::f32::consts::TAU;
+
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 use bevy_hanabi::prelude::*;
 use bevy_smud::{Frame, ShapeBundle};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use crate::{
   boid::{Boid, BoidConfig, TamedBoid},
   finish_line::{build_finish_line, FinishLineMaterial},
   grid::{build_grid, GridMaterial},
   moveable::{CollidedWithBounds, Moveable, MoveableBounds},
-  spawn_player, Player, PlayerInfo, Simulation, SimulationState,
+  obstacle::Obstacle,
+  scoring::{LevelScore, LevelStats},
+  spawn_player, Player, PlayerInfo, Simulation, SimulationFailed, SimulationRng, SimulationState,
 };
 
+mod loader;
 mod registry;
+mod validate;
 
+pub use loader::*;
 pub use registry::*;
+pub use validate::*;
 
 pub fn time_level(
   mut next_sim_state: ResMut<NextState<SimulationState>>,
@@ -20,10 +30,10 @@ pub fn time_level(
   lvl_reg: Res<LevelRegistry>,
   time: Res<Time>,
 ) {
-  let Some(level_id) = lvl_mgr.current_level else {
+  // nothing to time, reported by the other level systems
+  let Ok(lvl) = lvl_mgr.current(&lvl_reg) else {
     return;
   };
-  let lvl = lvl_reg.get_level(&level_id);
   lvl_mgr.watch.tick(time.delta());
 
   if let Some(time_goal) = lvl.time_goal {
@@ -41,12 +51,24 @@ pub fn check_if_level_complete(
   lvl_reg: Res<LevelRegistry>,
   qry_boid: Query<Entity, (With<Boid>, With<TamedBoid>, Without<Player>)>,
   mut player: ResMut<PlayerInfo>,
+  stats: Res<LevelStats>,
+  mut level_score: ResMut<LevelScore>,
+  mut failed: EventWriter<SimulationFailed>,
 ) {
   let Ok(t) = qry.get_single() else {
     return;
   };
-  let level_id = lvl_mgr.current_level.unwrap();
-  let lvl = lvl_reg.get_level(&level_id);
+  let Some(level_id) = lvl_mgr.current_level.clone() else {
+    failed.send(LevelRegistryError::NoCurrentLevel.into());
+    return;
+  };
+  let lvl = match lvl_reg.get_level(&level_id) {
+    Ok(lvl) => lvl,
+    Err(e) => {
+      failed.send(e.into());
+      return;
+    }
+  };
 
   if lvl.finish_bounds_box.distance_to_edge(t.translation.xy()) < 0.0 {
     // hit the finish line
@@ -60,7 +82,12 @@ pub fn check_if_level_complete(
     }
 
     lvl_mgr.level_complete = true;
-    player.score += score as u32;
+    lvl_mgr.unlocked.insert(level_id);
+    if let Some(next) = &lvl.next_level {
+      lvl_mgr.unlocked.insert(next.clone());
+    }
+    *level_score = LevelScore::calculate(lvl, &stats, score as u32, lvl_mgr.watch.elapsed());
+    player.score += level_score.total;
     next_sim_state.set(SimulationState::LevelComplete);
   }
 }
@@ -84,17 +111,25 @@ pub fn on_load_level_requested(
   lvl_reg: Res<LevelRegistry>,
   mut bconfig: ResMut<BoidConfig>,
   mut player: ResMut<PlayerInfo>,
+  mut rng: ResMut<SimulationRng>,
   mut bounds: ResMut<MoveableBounds>,
   mut meshes: ResMut<Assets<Mesh>>,
   mut grid_mats: ResMut<Assets<GridMaterial>>,
   mut fline_mats: ResMut<Assets<FinishLineMaterial>>,
   to_despawn: Query<Entity, With<Simulation>>,
   mut next_sim_state: ResMut<NextState<SimulationState>>,
+  mut failed: EventWriter<SimulationFailed>,
 ) {
-  let Some(id_to_load) = lvl_mgr.load_next else {
+  let Some(id_to_load) = lvl_mgr.load_next.take() else {
     return;
   };
-  let to_load = lvl_reg.get_level(&id_to_load);
+  let to_load = match lvl_reg.get_level(&id_to_load) {
+    Ok(lvl) => lvl,
+    Err(e) => {
+      failed.send(e.into());
+      return;
+    }
+  };
 
   // despawn all prev level entities
   for entity in &to_despawn {
@@ -104,8 +139,11 @@ pub fn on_load_level_requested(
   // reset
   player.in_boost_mode = false;
   lvl_mgr.watch.reset();
+  lvl_mgr.seed = lvl_mgr.next_seed.take();
+  rng.0 = ChaCha8Rng::seed_from_u64(lvl_mgr.seed.unwrap_or_default());
 
   bconfig.wander = to_load.wander;
+  bconfig.wall_avoidance = to_load.wall_avoidance;
 
   // spawn level entities
   if let Some(shape) = &to_load.bounds_sdf {
@@ -124,6 +162,25 @@ pub fn on_load_level_requested(
       Vec2::splat(frame_size),
     );
   }
+  for (i, (obstacle, shape)) in to_load
+    .bounds
+    .obstacles()
+    .iter()
+    .zip(to_load.obstacle_sdfs.iter())
+    .enumerate()
+  {
+    let mut transform = obstacle.transform_at(0.0);
+    transform.translation.z = -9.0;
+    cmd.spawn((
+      ShapeBundle {
+        shape: shape.clone(),
+        transform,
+        ..default()
+      },
+      Obstacle(i),
+      Simulation,
+    ));
+  }
   build_finish_line(
     &mut cmd,
     &mut meshes,
@@ -132,9 +189,13 @@ pub fn on_load_level_requested(
   );
 
   // update bounds
-  *bounds = to_load.bounds.clone();
+  *bounds = match &to_load.bake_bounds {
+    Some(settings) => to_load.bounds.bake(settings),
+    None => to_load.bounds.clone(),
+  };
 
-  spawn_player(&mut cmd, &player, to_load.starting_point, &bconfig).insert(Simulation);
+  spawn_player(&mut cmd, &player, to_load.starting_point, &bconfig, to_load.collision)
+    .insert(Simulation);
 
   for point in to_load.spawn_points.iter() {
     for x in 0..to_load.boids_per_spawn_point {
@@ -150,9 +211,15 @@ pub fn on_load_level_requested(
           ..default()
         })
         .insert((
-          Moveable::default(),
+          Moveable {
+            collision: to_load.collision,
+            ..default()
+          },
           Boid {
-            direction: Mat2::from_angle(x as f32).mul_vec2(Vec2::Y),
+            direction: match lvl_mgr.seed {
+              Some(_) => Vec2::from_angle(rng.0.gen_range(0.0..TAU)),
+              None => Mat2::from_angle(x as f32).mul_vec2(Vec2::Y),
+            },
             turning_speed: bconfig.wild_turn_speed,
             speed: bconfig.wild_speed,
             is_player: false,
@@ -173,25 +240,53 @@ pub fn on_load_level_requested(
 
   lvl_mgr.current_level = Some(id_to_load);
   lvl_mgr.level_complete = false;
-  lvl_mgr.load_next = None;
 
   next_sim_state.set(SimulationState::Simulating)
 }
 
+/// Despawns the current level and forgets progress so the next `StartGame` starts over
+pub fn unload_level(
+  mut cmd: Commands,
+  mut lvl_mgr: ResMut<LevelManager>,
+  mut player: ResMut<PlayerInfo>,
+  to_despawn: Query<Entity, With<Simulation>>,
+) {
+  for entity in &to_despawn {
+    cmd.entity(entity).despawn_recursive();
+  }
+  lvl_mgr.current_level = None;
+  lvl_mgr.level_complete = false;
+  lvl_mgr.load_next = None;
+  lvl_mgr.watch.reset();
+  player.score = 0;
+  player.in_boost_mode = false;
+}
+
 pub fn find_level_to_load(
   mut lvl_mgr: ResMut<LevelManager>,
   lvl_reg: Res<LevelRegistry>,
   mut next_sim_state: ResMut<NextState<SimulationState>>,
+  mut failed: EventWriter<SimulationFailed>,
 ) {
-  if let Some(cur_lvl) = lvl_mgr.current_level {
+  if lvl_mgr.load_next.is_some() {
+    // level was picked explicitly with StartLevel
+    return;
+  }
+  if let Some(cur_lvl) = lvl_mgr.current_level.clone() {
     if !lvl_mgr.level_complete {
       lvl_mgr.load_level(&cur_lvl);
     } else {
       // level complete
-      let cur = lvl_reg.get_level(&cur_lvl);
-      if let Some(next) = cur.next_level {
+      let cur = match lvl_reg.get_level(&cur_lvl) {
+        Ok(cur) => cur,
+        Err(e) => {
+          failed.send(e.into());
+          return;
+        }
+      };
+      if let Some(next) = &cur.next_level {
         // load next level
-        lvl_mgr.load_level(&next);
+        lvl_mgr.load_level(next);
       } else {
         // no more levels, GG
         next_sim_state.set(SimulationState::GameComplete)
@@ -199,9 +294,9 @@ pub fn find_level_to_load(
     }
   } else {
     // no curent level, load start level
-    let start = lvl_reg
-      .start_level
-      .expect("to have start level when starting a new game");
-    lvl_mgr.load_level(&start);
+    match lvl_reg.start_level() {
+      Ok(start) => lvl_mgr.load_level(start),
+      Err(e) => failed.send(e.into()),
+    }
   }
 }
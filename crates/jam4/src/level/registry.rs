@@ -1,16 +1,107 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+  borrow::Borrow,
+  collections::{HashMap, HashSet},
+  fmt,
+  str::FromStr,
+  time::Duration,
+};
 
 use bevy::{prelude::*, time::Stopwatch};
 use bevy_smud::SmudShape;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::moveable::MoveableBounds;
+use crate::{
+  boid::WallAvoidance,
+  moveable::{BakeSettings, CollisionResponse, MoveableBounds},
+  scoring::StarThresholds,
+};
 
-#[derive(PartialEq, Hash, Eq, Clone, Copy)]
-pub struct LevelId(u8);
+/// Level path namespaced by the module that registers it, e.g. `base_game:level5`
+/// ids are written as their path in level files, scripts and save data
+#[derive(PartialEq, Hash, Eq, PartialOrd, Ord, Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LevelId(String);
 
+#[derive(Debug, Error, PartialEq)]
+#[error("invalid level id {0:?}, expected <module>:<level>")]
+pub struct InvalidLevelId(String);
+
+impl LevelId {
+  pub fn new(module: &str, name: &str) -> Result<Self, InvalidLevelId> {
+    format!("{module}:{name}").parse()
+  }
+
+  /// `path` without a module is looked up in `module`
+  pub fn resolve(path: &str, module: &str) -> Result<Self, InvalidLevelId> {
+    if path.contains(':') {
+      path.parse()
+    } else {
+      Self::new(module, path)
+    }
+  }
+
+  pub fn module(&self) -> &str {
+    self.0.split_once(':').map_or("", |(module, _)| module)
+  }
+
+  pub fn name(&self) -> &str {
+    self.0.split_once(':').map_or("", |(_, name)| name)
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl FromStr for LevelId {
+  type Err = InvalidLevelId;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let valid_part = |part: &str| {
+      !part.is_empty()
+        && part
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    match s.split_once(':') {
+      Some((module, name)) if valid_part(module) && valid_part(name) => Ok(Self(s.to_owned())),
+      _ => Err(InvalidLevelId(s.to_owned())),
+    }
+  }
+}
+
+impl TryFrom<String> for LevelId {
+  type Error = InvalidLevelId;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<LevelId> for String {
+  fn from(value: LevelId) -> Self {
+    value.0
+  }
+}
+
+impl fmt::Display for LevelId {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+/// lets `LevelRegistry::levels` be queried by path
+impl Borrow<str> for LevelId {
+  fn borrow(&self) -> &str {
+    &self.0
+  }
+}
+
+/// Numbered levels of the base game, `3` is `base_game:level3`
 impl From<u8> for LevelId {
   fn from(value: u8) -> Self {
-    Self(value)
+    Self(format!("base_game:level{value}"))
   }
 }
 
@@ -23,9 +114,13 @@ pub struct LevelRegistry {
 pub struct LevelInfo {
   pub name: String,
   pub bounds: MoveableBounds,
+  /// bounds are sampled into a grid when the level is loaded
+  pub bake_bounds: Option<BakeSettings>,
   pub finish_bounds_box: MoveableBounds,
   pub finish_bounds: Vec4,
   pub bounds_sdf: Option<SmudShape>,
+  /// renders `bounds.obstacles()`, in the same order
+  pub obstacle_sdfs: Vec<SmudShape>,
   pub starting_point: Vec2,
   pub next_level: Option<LevelId>,
   pub spawn_points: Vec<Vec2>,
@@ -33,11 +128,109 @@ pub struct LevelInfo {
   pub rescue_goal: Option<u16>,
   pub time_goal: Option<Duration>,
   pub wander: bool,
+  pub wall_avoidance: WallAvoidance,
+  /// how the player and the boids react to running into walls
+  pub collision: CollisionResponse,
+  /// derived from the boid count and rescue goal if not set
+  pub star_thresholds: Option<StarThresholds>,
+}
+
+#[derive(Debug, Error)]
+pub enum LevelRegistryError {
+  #[error("level {0} is already registered as \"{1}\"")]
+  Duplicate(LevelId, String),
+  #[error("level {0} is not registered")]
+  NotFound(LevelId),
+  #[error("no level is being played")]
+  NoCurrentLevel,
+  #[error("no start level has been set")]
+  NoStartLevel,
+  #[error("level {0} continues with {1}, which is not registered")]
+  BrokenLink(LevelId, LevelId),
 }
 
 impl LevelRegistry {
-  pub fn get_level(&self, id: &LevelId) -> &LevelInfo {
-    self.levels.get(id).expect("Level should be in registrry")
+  /// Adds a level, if the id is already taken the first level is kept
+  pub fn register(&mut self, id: LevelId, level: LevelInfo) -> Result<(), LevelRegistryError> {
+    if let Some(existing) = self.levels.get(&id) {
+      return Err(LevelRegistryError::Duplicate(id, existing.name.clone()));
+    }
+    self.levels.insert(id, level);
+    Ok(())
+  }
+
+  /// Adds a level or swaps out the one registered under `id`, returning the previous level
+  /// only for levels that are meant to change, like the editor's play-test level
+  pub fn replace(&mut self, id: LevelId, level: LevelInfo) -> Option<LevelInfo> {
+    self.levels.insert(id, level)
+  }
+
+  pub fn unregister(&mut self, id: &LevelId) -> Option<LevelInfo> {
+    self.levels.remove(id)
+  }
+
+  pub fn get_level(&self, id: &LevelId) -> Result<&LevelInfo, LevelRegistryError> {
+    self
+      .levels
+      .get(id)
+      .ok_or_else(|| LevelRegistryError::NotFound(id.clone()))
+  }
+
+  pub fn start_level(&self) -> Result<&LevelId, LevelRegistryError> {
+    self
+      .start_level
+      .as_ref()
+      .ok_or(LevelRegistryError::NoStartLevel)
+  }
+
+  /// Checks that the start level and every `next_level` link point to registered levels
+  pub fn validate(&self) -> Result<(), Vec<LevelRegistryError>> {
+    let mut errors = Vec::new();
+    if let Err(e) = self.start_level().and_then(|id| self.get_level(id)) {
+      errors.push(e);
+    }
+    let mut ids: Vec<_> = self.levels.keys().collect();
+    ids.sort();
+    for id in ids {
+      if let Some(next) = &self.levels[id].next_level {
+        if !self.levels.contains_key(next) {
+          errors.push(LevelRegistryError::BrokenLink(id.clone(), next.clone()));
+        }
+      }
+    }
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+
+  /// Lookup by path, e.g. `base_game:level5`
+  pub fn find_level(&self, path: &str) -> Option<&LevelInfo> {
+    self.levels.get(path)
+  }
+
+  /// Levels in play order, following `next_level` from the start level
+  /// levels not reachable that way come last, ordered by id
+  pub fn ordered_levels(&self) -> Vec<LevelId> {
+    let mut ordered = Vec::new();
+    let mut next = self.start_level.clone();
+    while let Some(id) = next {
+      if ordered.contains(&id) || !self.levels.contains_key(&id) {
+        break;
+      }
+      next = self.levels[&id].next_level.clone();
+      ordered.push(id);
+    }
+    let mut rest: Vec<_> = self
+      .levels
+      .keys()
+      .filter(|id| !ordered.contains(id))
+      .cloned()
+      .collect();
+    rest.sort();
+    ordered.extend(rest);
+    ordered
   }
 }
 
@@ -46,11 +239,71 @@ pub struct LevelManager {
   pub current_level: Option<LevelId>,
   pub level_complete: bool,
   pub load_next: Option<LevelId>,
-  pub watch: Stopwatch
+  pub watch: Stopwatch,
+  /// seed used to spawn the current level, boids start in a fixed pattern if not set
+  pub seed: Option<u64>,
+  /// seed for the next level load, set when playing back a seeded replay
+  pub next_seed: Option<u64>,
+  /// levels that can be started from the level select, the start level is always unlocked
+  pub unlocked: HashSet<LevelId>,
 }
 
 impl LevelManager {
+  /// Level that is being played
+  pub fn current<'a>(
+    &self,
+    lvl_reg: &'a LevelRegistry,
+  ) -> Result<&'a LevelInfo, LevelRegistryError> {
+    let id = self
+      .current_level
+      .as_ref()
+      .ok_or(LevelRegistryError::NoCurrentLevel)?;
+    lvl_reg.get_level(id)
+  }
+
   pub fn load_level(&mut self, level_id: &LevelId) {
-    self.load_next = Some(*level_id);
+    self.load_next = Some(level_id.clone());
+  }
+
+  pub fn is_unlocked(&self, level_id: &LevelId, lvl_reg: &LevelRegistry) -> bool {
+    lvl_reg.levels.contains_key(level_id)
+      && (lvl_reg.start_level.as_ref() == Some(level_id) || self.unlocked.contains(level_id))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tests::corridor_level;
+
+  fn named(name: &str) -> LevelInfo {
+    LevelInfo {
+      name: name.to_owned(),
+      ..corridor_level()
+    }
+  }
+
+  #[test]
+  fn register_keeps_the_first_level() {
+    let mut lvl_reg = LevelRegistry::default();
+    let id = LevelId::from(1);
+    assert!(lvl_reg.register(id.clone(), named("first")).is_ok());
+    assert!(matches!(
+      lvl_reg.register(id.clone(), named("second")),
+      Err(LevelRegistryError::Duplicate(_, name)) if name == "first"
+    ));
+    assert_eq!(lvl_reg.levels[&id].name, "first");
+  }
+
+  #[test]
+  fn replace_swaps_the_level() {
+    let mut lvl_reg = LevelRegistry::default();
+    let id = LevelId::from(1);
+    assert!(lvl_reg.replace(id.clone(), named("first")).is_none());
+    let previous = lvl_reg.replace(id.clone(), named("second"));
+    assert_eq!(previous.map(|lvl| lvl.name), Some("first".to_owned()));
+    assert_eq!(lvl_reg.levels[&id].name, "second");
+    assert!(lvl_reg.unregister(&id).is_some());
+    assert!(lvl_reg.levels.is_empty());
   }
 }
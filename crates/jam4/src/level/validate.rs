@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use crate::moveable::MoveableBounds;
+
+use super::LevelInfo;
+
+/// cells visited before a level is considered open to the outside
+const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum LevelProblem {
+  #[error("starting or spawn point {0} is inside a wall")]
+  EmbeddedSpawn(Vec2),
+  #[error("there is no room to move around the starting point {0}")]
+  BlockedStart(Vec2),
+  #[error("spawn point {0} can not be reached from the start")]
+  UnreachableSpawnPoint(Vec2),
+  #[error("the finish can not be reached from the start")]
+  UnreachableFinish,
+  #[error("the level is not enclosed by walls")]
+  Unbounded,
+}
+
+/// Space reachable from the starting point, sampled on a grid of `clearance` sized cells
+///
+/// a cell is open when it is at least `clearance` away from the walls,
+/// so the straight line between two neighbouring open cells stays clear of the walls too
+pub struct Reachability {
+  clearance: f32,
+  cells: HashSet<IVec2>,
+  finish_reached: bool,
+}
+
+impl Reachability {
+  /// Flood fills the level from every open cell near the starting point
+  /// the fill stops at the finish since the level ends there
+  pub fn flood_fill(level: &LevelInfo, clearance: f32) -> Result<Self, LevelProblem> {
+    let bounds = &level.bounds;
+    let position = |cell: IVec2| cell.as_vec2() * clearance;
+    let is_open = |cell: IVec2| bounds.distance_to_edge(position(cell)) >= clearance;
+
+    let mut cells = HashSet::new();
+    let mut pending: Vec<_> = Self::cells_near(level.starting_point, clearance)
+      .filter(|cell| is_open(*cell))
+      .collect();
+    if pending.is_empty() {
+      return Err(LevelProblem::BlockedStart(level.starting_point));
+    }
+    cells.extend(pending.iter().copied());
+
+    let mut finish_reached = false;
+    while let Some(cell) = pending.pop() {
+      if level.finish_bounds_box.distance_to_edge(position(cell)) < clearance {
+        finish_reached = true;
+        continue;
+      }
+      for next in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|d| cell + d) {
+        if !cells.contains(&next) && is_open(next) {
+          cells.insert(next);
+          pending.push(next);
+        }
+      }
+      if cells.len() > MAX_CELLS {
+        return Err(LevelProblem::Unbounded);
+      }
+    }
+
+    Ok(Self {
+      clearance,
+      cells,
+      finish_reached,
+    })
+  }
+
+  /// grid cells within two cells of `p`
+  fn cells_near(p: Vec2, clearance: f32) -> impl Iterator<Item = IVec2> {
+    let center = (p / clearance).round().as_ivec2();
+    (-2..=2)
+      .flat_map(move |x| (-2..=2).map(move |y| center + IVec2::new(x, y)))
+      .filter(move |cell| cell.as_vec2().distance(p / clearance) <= 2.0)
+  }
+
+  pub fn is_reachable(&self, p: Vec2) -> bool {
+    Self::cells_near(p, self.clearance).any(|cell| self.cells.contains(&cell))
+  }
+
+  pub fn finish_reached(&self) -> bool {
+    self.finish_reached
+  }
+}
+
+/// Checks that something turning in circles of `clearance` radius
+/// can get from the start to every spawn point and to the finish,
+/// and that none of those points are inside a wall
+/// moving obstacles are checked where they are when the level starts
+pub fn validate_level(level: &LevelInfo, clearance: f32) -> Result<(), Vec<LevelProblem>> {
+  if let MoveableBounds::None = level.bounds {
+    return Ok(());
+  }
+  // being close enough to open space is not enough, things spawned in a wall stay stuck there
+  let embedded = |p: &Vec2| level.bounds.distance_to_edge(*p) < 0.0;
+  let mut problems: Vec<_> = std::iter::once(&level.starting_point)
+    .chain(level.spawn_points.iter())
+    .filter(|p| embedded(p))
+    .map(|p| LevelProblem::EmbeddedSpawn(*p))
+    .collect();
+
+  let reachable = match Reachability::flood_fill(level, clearance) {
+    Ok(reachable) => reachable,
+    Err(e) => {
+      problems.push(e);
+      return Err(problems);
+    }
+  };
+  problems.extend(
+    level
+      .spawn_points
+      .iter()
+      .filter(|p| !embedded(p) && !reachable.is_reachable(**p))
+      .map(|p| LevelProblem::UnreachableSpawnPoint(*p)),
+  );
+  if !reachable.finish_reached() {
+    problems.push(LevelProblem::UnreachableFinish);
+  }
+
+  if problems.is_empty() {
+    Ok(())
+  } else {
+    Err(problems)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{shape::Shape, tests::corridor_level};
+
+  const CLEARANCE: f32 = 100.;
+
+  #[test]
+  fn open_level_is_valid() {
+    assert_eq!(validate_level(&corridor_level(), CLEARANCE), Ok(()));
+  }
+
+  #[test]
+  fn spawn_point_inside_a_wall_is_embedded() {
+    // close enough to open space to count as reachable, but inside the corridor wall
+    let level = LevelInfo {
+      spawn_points: vec![Vec2::ZERO, Vec2::new(1050., 0.)],
+      ..corridor_level()
+    };
+    assert_eq!(
+      validate_level(&level, CLEARANCE),
+      Err(vec![LevelProblem::EmbeddedSpawn(Vec2::new(1050., 0.))])
+    );
+  }
+
+  #[test]
+  fn starting_point_inside_a_wall_is_embedded() {
+    let level = LevelInfo {
+      starting_point: Vec2::new(-1100., -4000.),
+      ..corridor_level()
+    };
+    assert_eq!(
+      validate_level(&level, CLEARANCE),
+      Err(vec![LevelProblem::EmbeddedSpawn(Vec2::new(-1100., -4000.))])
+    );
+  }
+
+  #[test]
+  fn walled_off_spawn_point_is_unreachable() {
+    let mut level = corridor_level();
+    let pocket = Shape::Box(Vec2::splat(8000.))
+      .subtract(Shape::Box(Vec2::new(1000., 5000.)))
+      .subtract(Shape::rect(Vec2::new(0., 5000.), Vec2::splat(1000.)))
+      .subtract(Shape::rect(Vec2::new(4000., 0.), Vec2::splat(1000.)));
+    level.bounds = MoveableBounds::from_sdf(pocket);
+    level.spawn_points = vec![Vec2::new(4000., 0.)];
+    assert_eq!(
+      validate_level(&level, CLEARANCE),
+      Err(vec![LevelProblem::UnreachableSpawnPoint(Vec2::new(4000., 0.))])
+    );
+  }
+}
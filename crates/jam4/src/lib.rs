@@ -1,4 +1,13 @@
-use bevy::{prelude::*, sprite::Material2dPlugin};
+use std::time::Duration;
+
+use bevy::{
+  app::RunFixedUpdateLoop,
+  gizmos::GizmoPlugin,
+  prelude::*,
+  sprite::Material2dPlugin,
+  time::{run_fixed_update_schedule, TimeUpdateStrategy},
+};
+use bevy_hanabi::EffectAsset;
 use bevy_smud::SmudPlugin;
 
 pub mod boid;
@@ -8,27 +17,46 @@ mod grid;
 pub mod level;
 mod mods;
 pub mod moveable;
+pub mod obstacle;
 mod player;
+pub mod replay;
+pub mod save;
+pub mod scoring;
+pub mod shape;
 mod state;
 
 use boid::{
   calc_tamed_boids, calculate_boid_direction, despawn_collided_boids, draw_boid_gizmos,
-  update_boid_velocity, update_tamed_boids, BoidConfig,
+  follow_deflections, update_boid_index, update_boid_velocity, update_tamed_boids, BoidConfig,
+  BoidIndex,
 };
 pub use components::*;
 use finish_line::FinishLineMaterial;
 use grid::GridMaterial;
 use level::{
   check_if_game_over, check_if_level_complete, find_level_to_load, on_load_level_requested,
-  time_level, LevelManager, LevelRegistry,
+  register_loaded_levels, time_level, unload_level, LevelAsset, LevelLoader, LevelManager,
+  LevelRegistry,
 };
 pub use mods::*;
-use moveable::{move_moveables, MoveableBounds};
+use moveable::{
+  interpolate_transforms, move_moveables, record_fixed_transforms, restore_fixed_transforms,
+  MoveableBounds,
+};
+use obstacle::animate_obstacles;
 pub use player::*;
+use replay::{
+  process_replay_commands, record_player_input, start_recording, ReplayCommand, ReplayManager,
+};
+use save::{persist_save_data, record_level_result, SaveData};
+use scoring::{reset_level_stats, track_boost_usage, LevelScore, LevelStats};
 pub use state::*;
 
 pub trait Jam4Extensions {
   fn add_jam_game(&mut self) -> &mut Self;
+  /// Simulation only, for tests and batch runs on top of `MinimalPlugins` and `AssetPlugin`
+  /// every `App::update` advances time by exactly one `timestep`, progress is never saved
+  fn add_headless_jam_game(&mut self, timestep: Duration) -> &mut Self;
 }
 
 impl Jam4Extensions for App {
@@ -37,52 +65,271 @@ impl Jam4Extensions for App {
       .add_plugins(SmudPlugin)
       .add_plugins(Material2dPlugin::<GridMaterial>::default())
       .add_plugins(Material2dPlugin::<FinishLineMaterial>::default())
-      .init_resource::<PlayerInfo>()
-      .init_resource::<ModManager>()
-      .init_resource::<MoveableBounds>()
-      .init_resource::<BoidConfig>()
-      .init_resource::<LevelRegistry>()
-      .init_resource::<LevelManager>()
-      .add_state::<SimulationState>()
-      .add_event::<GameControlCommand>()
-      .add_systems(OnExit(SimulationState::Disabled), register_mods)
-      .add_systems(OnEnter(SimulationState::Initializing), run_mod_init)
       .add_systems(
-        OnEnter(SimulationState::ChoosingLevel),
-        (
-          find_level_to_load,
-          on_load_level_requested,
-          apply_deferred,
-          run_mod_setup,
-        )
-          .chain(),
+        OnEnter(SimulationState::LevelComplete),
+        persist_save_data.after(record_level_result),
+      );
+    add_simulation(self)
+  }
+
+  fn add_headless_jam_game(&mut self, timestep: Duration) -> &mut Self {
+    self
+      .insert_resource(Time::<Fixed>::from_duration(timestep))
+      .insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+      // asset types usually registered by the render, audio and particle plugins
+      // entities still get their render components, they are just never drawn
+      .init_asset::<Shader>()
+      .init_asset::<Mesh>()
+      .init_asset::<ColorMaterial>()
+      .init_asset::<AudioSource>()
+      .init_asset::<EffectAsset>()
+      .init_asset::<GridMaterial>()
+      .init_asset::<FinishLineMaterial>()
+      .add_plugins(GizmoPlugin);
+    add_simulation(self)
+  }
+}
+
+fn add_simulation(app: &mut App) -> &mut App {
+  app
+    .init_resource::<PlayerInfo>()
+    .init_resource::<PlayerInput>()
+    .init_resource::<ReplayManager>()
+    .init_resource::<SimulationRng>()
+    .init_resource::<SaveData>()
+    .init_resource::<LevelStats>()
+    .init_resource::<LevelScore>()
+    .init_resource::<ModManager>()
+    .init_resource::<ScriptEngine>()
+    .init_resource::<MoveableBounds>()
+    .init_resource::<BoidConfig>()
+    .init_resource::<BoidIndex>()
+    .init_resource::<LevelRegistry>()
+    .init_resource::<LevelManager>()
+    .init_resource::<SimulationErrors>()
+    .init_asset::<LevelAsset>()
+    .init_asset_loader::<LevelLoader>()
+    .init_asset::<ModAsset>()
+    .init_asset_loader::<ModLoader>()
+    .add_state::<SimulationState>()
+    .add_event::<GameControlCommand>()
+    .add_event::<CommandRejected>()
+    .add_event::<SimulationFailed>()
+    .add_event::<ReplayCommand>()
+    .add_systems(OnExit(SimulationState::Disabled), register_mods)
+    .add_systems(OnEnter(SimulationState::Initializing), run_mod_init)
+    .add_systems(
+      OnEnter(SimulationState::ChoosingLevel),
+      (
+        find_level_to_load,
+        on_load_level_requested,
+        apply_deferred,
+        run_mod_setup,
       )
-      .add_systems(
-        FixedUpdate,
+        .chain(),
+    )
+    // OnEnter(Simulating) also runs when resuming from a pause
+    .add_systems(
+      OnTransition {
+        from: SimulationState::ChoosingLevel,
+        to: SimulationState::Simulating,
+      },
+      (start_recording, reset_level_stats),
+    )
+    .add_systems(OnEnter(SimulationState::LevelComplete), record_level_result)
+    .add_systems(
+      OnTransition {
+        from: SimulationState::Paused,
+        to: SimulationState::Ready,
+      },
+      unload_level,
+    )
+    .add_systems(
+      OnTransition {
+        from: SimulationState::Error,
+        to: SimulationState::Ready,
+      },
+      unload_level,
+    )
+    .add_systems(OnExit(SimulationState::Error), clear_simulation_errors)
+    // everything that moves does so on the fixed step, so runs don't depend on the frame rate
+    .add_systems(
+      FixedUpdate,
+      (
+        restore_fixed_transforms,
         (
+          animate_obstacles,
+          follow_deflections,
+          record_player_input,
+          steer_player,
+          track_boost_usage,
+          update_boid_index,
           calc_tamed_boids,
           apply_deferred,
           update_tamed_boids,
           calculate_boid_direction,
           update_boid_velocity,
-          (
-            time_level,
-            check_if_game_over,
-            despawn_collided_boids,
-            check_if_level_complete,
-          ),
         )
           .chain()
           .run_if(in_state(SimulationState::Simulating)),
+        (move_moveables, apply_deferred)
+          .chain()
+          .run_if(not(in_state(SimulationState::Paused))),
+        (
+          time_level,
+          check_if_game_over,
+          despawn_collided_boids,
+          check_if_level_complete,
+        )
+          .run_if(in_state(SimulationState::Simulating)),
+        record_fixed_transforms,
       )
-      .add_systems(
-        Update,
+        .chain(),
+    )
+    // before `Update`, so everything there already sees where things are drawn
+    .add_systems(
+      RunFixedUpdateLoop,
+      interpolate_transforms.after(run_fixed_update_schedule),
+    )
+    .add_systems(
+      Update,
+      (
         (
-          move_moveables,
+          process_replay_commands,
           process_game_control_commands,
-          (run_mod_update, draw_boid_gizmos).run_if(in_state(SimulationState::Simulating)),
-          wait_until_initialization_complete.run_if(in_state(SimulationState::Initializing)),
-        ),
-      )
+          process_simulation_failures,
+        )
+          .chain(),
+        (run_mod_update, draw_boid_gizmos).run_if(in_state(SimulationState::Simulating)),
+        (register_loaded_levels, wait_until_initialization_complete)
+          .chain()
+          .run_if(in_state(SimulationState::Initializing)),
+      ),
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::{
+    boid::WallAvoidance,
+    level::{LevelId, LevelInfo},
+    moveable::CollisionResponse,
+    shape::Shape,
+  };
+
+  const MAX_UPDATES: usize = 2000;
+
+  pub(crate) fn corridor_id() -> LevelId {
+    LevelId::new("test", "corridor").expect("test level ids are valid")
+  }
+
+  /// Straight corridor from the start at the bottom to the finish at the top
+  pub(crate) fn corridor_level() -> LevelInfo {
+    let finish = Shape::rect(Vec2::new(0., 5000.), Vec2::splat(1000.));
+    let walls = Shape::Box(Vec2::splat(8000.)).subtract(Shape::Box(Vec2::new(1000., 5000.)));
+    LevelInfo {
+      name: "Corridor".to_owned(),
+      bounds: MoveableBounds::from_sdf(walls.subtract(finish.clone())),
+      bake_bounds: None,
+      finish_bounds_box: MoveableBounds::from_sdf(finish),
+      finish_bounds: Vec4::new(0., 5000., 1000., 1000.),
+      bounds_sdf: None,
+      obstacle_sdfs: Vec::new(),
+      starting_point: Vec2::new(0., -4000.),
+      next_level: None,
+      spawn_points: vec![Vec2::ZERO],
+      boids_per_spawn_point: 10,
+      rescue_goal: None,
+      time_goal: None,
+      wander: false,
+      wall_avoidance: WallAvoidance::Probes,
+      collision: CollisionResponse::Stop,
+      star_thresholds: None,
+    }
+  }
+
+  /// Initialized headless game with `level` as the only, starting level
+  pub(crate) fn headless_app(level: LevelInfo) -> App {
+    let mut app = App::new();
+    app
+      .add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+          file_path: "../../assets".to_owned(),
+          ..default()
+        },
+      ))
+      .add_headless_jam_game(Duration::from_secs_f32(1. / 60.))
+      .insert_resource(LevelRegistry {
+        levels: [(corridor_id(), level)].into(),
+        start_level: Some(corridor_id()),
+      });
+    app.world.send_event(GameControlCommand::Initialize);
+    run_until(&mut app, SimulationState::Ready);
+    app
+  }
+
+  fn state(app: &App) -> SimulationState {
+    *app.world.resource::<State<SimulationState>>().get()
+  }
+
+  pub(crate) fn run_until(app: &mut App, target: SimulationState) {
+    for _ in 0..MAX_UPDATES {
+      if state(app) == target {
+        return;
+      }
+      app.update();
+    }
+    panic!("expected {target:?}, still in {:?}", state(app));
+  }
+
+  /// Feeds `script` the number of the update and steers the player with its input until the
+  /// level is over, returns the state it ended in
+  pub(crate) fn play(app: &mut App, script: impl Fn(usize) -> PlayerInput) -> SimulationState {
+    for update in 0..MAX_UPDATES {
+      if state(app) != SimulationState::Simulating {
+        return state(app);
+      }
+      *app.world.resource_mut::<PlayerInput>() = script(update);
+      app.update();
+    }
+    panic!("level was not over after {MAX_UPDATES} updates");
+  }
+
+  /// Swerves left and right, then flies straight up the corridor
+  pub(crate) fn weave(update: usize) -> PlayerInput {
+    let turn = match update {
+      0..=19 | 60..=79 => 1.,
+      20..=59 => -1.,
+      _ => 0.,
+    };
+    PlayerInput { turn, boost: false }
+  }
+
+  pub(crate) fn start_game(app: &mut App) {
+    app.world.send_event(GameControlCommand::StartGame);
+    run_until(app, SimulationState::Simulating);
+  }
+
+  #[test]
+  fn level_is_completable_by_scripted_input() {
+    let mut app = headless_app(corridor_level());
+    start_game(&mut app);
+    assert_eq!(play(&mut app, weave), SimulationState::LevelComplete);
+  }
+
+  #[test]
+  fn scripted_input_can_crash_into_a_wall() {
+    let mut app = headless_app(corridor_level());
+    start_game(&mut app);
+    let turn_into_wall = |update: usize| PlayerInput {
+      turn: if update < 60 { 1. } else { 0. },
+      boost: false,
+    };
+    assert_eq!(
+      play(&mut app, turn_into_wall),
+      SimulationState::GameOver(GameOverReason::OutOfBounds)
+    );
   }
 }
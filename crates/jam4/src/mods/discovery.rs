@@ -0,0 +1,117 @@
+use bevy::{
+  asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+  prelude::*,
+  utils::BoxedFuture,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{GameModule, GameModuleDescriptor, ModuleInfo, ScriptEngine, ScriptGameModule};
+
+/// Folder scanned for mods, one sub folder per mod
+pub const MODS_FOLDER: &str = "mods";
+
+/// Description of a mod, see `assets/mods/<mod>/<mod>.mod.ron`
+///
+/// ```ron
+/// (
+///   name: "faster_boids",
+///   version: "0.1.0",
+///   dependencies: ["base_game"],
+///   entry: "main.rhai",
+/// )
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ModManifest {
+  pub name: String,
+  pub version: String,
+  /// names of modules that run before this one, `base_game` for the base game
+  #[serde(default)]
+  pub dependencies: Vec<String>,
+  /// script path, relative to the manifest
+  pub entry: String,
+}
+
+/// Manifest together with its compiled entry script
+/// script errors are kept so they can be reported per mod
+#[derive(Asset, TypePath, Clone)]
+pub struct ModAsset {
+  pub manifest: ModManifest,
+  pub module: Result<ScriptGameModule, String>,
+}
+
+impl ModAsset {
+  pub fn descriptor(&self) -> Result<GameModuleDescriptor, String> {
+    let module = self.module.clone()?;
+    Ok(GameModuleDescriptor {
+      info: ModuleInfo {
+        id: self.manifest.name.clone(),
+        version: self.manifest.version.clone(),
+        depends_on: self.manifest.dependencies.clone(),
+      },
+      module: GameModule::Script(module),
+    })
+  }
+}
+
+#[derive(Debug, Error)]
+pub enum ModLoaderError {
+  #[error("could not read mod manifest: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("could not parse mod manifest: {0}")]
+  Parse(#[from] ron::error::SpannedError),
+}
+
+pub struct ModLoader {
+  engine: ScriptEngine,
+}
+
+impl FromWorld for ModLoader {
+  fn from_world(world: &mut World) -> Self {
+    Self {
+      engine: world
+        .get_resource_or_insert_with(ScriptEngine::default)
+        .clone(),
+    }
+  }
+}
+
+impl AssetLoader for ModLoader {
+  type Asset = ModAsset;
+  type Settings = ();
+  type Error = ModLoaderError;
+
+  fn load<'a>(
+    &'a self,
+    reader: &'a mut Reader,
+    _settings: &'a Self::Settings,
+    load_context: &'a mut LoadContext,
+  ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+    Box::pin(async move {
+      let mut bytes = Vec::new();
+      reader.read_to_end(&mut bytes).await?;
+      let manifest = ron::de::from_bytes::<ModManifest>(&bytes)?;
+
+      let entry = load_context
+        .path()
+        .parent()
+        .unwrap_or(std::path::Path::new(""))
+        .join(&manifest.entry);
+      let module = match load_context.read_asset_bytes(entry).await {
+        Ok(source) => String::from_utf8(source)
+          .map_err(|e| format!("{}: {e}", manifest.entry))
+          .and_then(|source| {
+            ScriptGameModule::compile(&self.engine, &manifest.name, &source)
+              .map_err(|e| e.to_string())
+          }),
+        Err(e) => Err(e.to_string()),
+      };
+
+      Ok(ModAsset { manifest, module })
+    })
+  }
+
+  fn extensions(&self) -> &[&str] {
+    &["mod.ron"]
+  }
+}
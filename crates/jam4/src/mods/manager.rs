@@ -1,10 +1,32 @@
+use std::collections::HashSet;
+
 use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use thiserror::Error;
+
+use super::ScriptGameModule;
 
 #[derive(Default, Resource)]
 pub struct ModManager {
   modules: Vec<GameModuleDescriptor>,
 }
 
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ModError {
+  #[error("another module is already registered as {0}")]
+  Duplicate(String),
+  #[error("missing dependency {0}")]
+  MissingDependency(String),
+  #[error("dependency {0} failed to load")]
+  DependencyFailed(String),
+  #[error("dependency cycle")]
+  Cycle,
+}
+
+/// each module runs its systems in its own schedule,
+/// so commands are applied before the modules depending on it run
+#[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
+struct ModuleSchedule(String);
+
 impl ModManager {
   pub fn clear(&mut self) -> &mut Self {
     self.modules.clear();
@@ -16,49 +38,133 @@ impl ModManager {
     self
   }
 
-  pub fn build_init_schedule(&self, label: impl ScheduleLabel) -> Schedule {
-    let mut sched = Schedule::new(label);
+  /// Modules in dependency order, ties keep the registration order
+  /// modules that cannot be loaded are left out and returned as errors by id,
+  /// together with every module depending on them
+  pub fn resolve(&self) -> (Vec<&GameModuleDescriptor>, Vec<(String, ModError)>) {
+    let mut errors = Vec::new();
+    let mut ids = HashSet::new();
+    let mut pending = Vec::new();
     for module in self.modules.iter() {
-      module.register_init(&mut sched);
+      if ids.insert(module.info.id.as_str()) {
+        pending.push(module);
+      } else {
+        let id = module.info.id.clone();
+        errors.push((id.clone(), ModError::Duplicate(id)));
+      }
     }
-    sched
+
+    let mut order = Vec::new();
+    let mut loaded = HashSet::new();
+    let mut failed = HashSet::new();
+    loop {
+      let mut progress = false;
+      for module in std::mem::take(&mut pending) {
+        let deps = &module.info.depends_on;
+        let error = if let Some(dep) = deps.iter().find(|d| !ids.contains(d.as_str())) {
+          ModError::MissingDependency(dep.clone())
+        } else if let Some(dep) = deps.iter().find(|d| failed.contains(d.as_str())) {
+          ModError::DependencyFailed(dep.clone())
+        } else if deps.iter().all(|d| loaded.contains(d.as_str())) {
+          progress = true;
+          loaded.insert(module.info.id.as_str());
+          order.push(module);
+          continue;
+        } else {
+          pending.push(module);
+          continue;
+        };
+        progress = true;
+        failed.insert(module.info.id.as_str());
+        errors.push((module.info.id.clone(), error));
+      }
+      if !progress {
+        break;
+      }
+    }
+    // whatever is left waits on itself
+    errors.extend(
+      pending
+        .into_iter()
+        .map(|module| (module.info.id.clone(), ModError::Cycle)),
+    );
+
+    (order, errors)
   }
-  pub fn build_setup_schedule(&self, label: impl ScheduleLabel) -> Schedule {
+
+  fn build_schedule(
+    &self,
+    label: impl ScheduleLabel,
+    register: fn(&GameModule, &mut Schedule),
+  ) -> Schedule {
+    let mut module_scheds: Vec<Schedule> = self
+      .resolve()
+      .0
+      .into_iter()
+      .map(|module| {
+        let mut sched = Schedule::new(ModuleSchedule(module.info.id.clone()));
+        register(&module.module, &mut sched);
+        sched
+      })
+      .collect();
+
     let mut sched = Schedule::new(label);
-    for module in self.modules.iter() {
-      module.register_setup(&mut sched);
-    }
+    sched.add_systems(move |world: &mut World| {
+      for module_sched in module_scheds.iter_mut() {
+        module_sched.run(world);
+      }
+    });
     sched
   }
+
+  pub fn build_init_schedule(&self, label: impl ScheduleLabel) -> Schedule {
+    self.build_schedule(label, GameModule::register_init)
+  }
+  pub fn build_setup_schedule(&self, label: impl ScheduleLabel) -> Schedule {
+    self.build_schedule(label, GameModule::register_setup)
+  }
   pub fn build_update_schedule(&self, label: impl ScheduleLabel) -> Schedule {
-    let mut sched = Schedule::new(label);
-    for module in self.modules.iter() {
-      module.register_update(&mut sched);
-    }
-    sched
+    self.build_schedule(label, GameModule::register_update)
   }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct ModuleInfo {
+  pub id: String,
+  pub version: String,
+  /// ids of modules whose systems run before this one
+  pub depends_on: Vec<String>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct GameModuleDescriptor {
+  pub info: ModuleInfo,
+  pub module: GameModule,
+}
+
 #[derive(Clone, PartialEq)]
-pub enum GameModuleDescriptor {
+pub enum GameModule {
   Native(NativeGameModule),
   Script(ScriptGameModule),
 }
 
-impl GameModuleDescriptor {
+impl GameModule {
   pub fn register_init(&self, sched: &mut Schedule) {
-    if let &GameModuleDescriptor::Native(native_mod) = &self {
-      (native_mod.register_init)(sched);
+    match self {
+      GameModule::Native(native_mod) => (native_mod.register_init)(sched),
+      GameModule::Script(script_mod) => script_mod.register_init(sched),
     }
   }
   pub fn register_setup(&self, sched: &mut Schedule) {
-    if let &GameModuleDescriptor::Native(native_mod) = &self {
-      (native_mod.register_setup)(sched);
+    match self {
+      GameModule::Native(native_mod) => (native_mod.register_setup)(sched),
+      GameModule::Script(script_mod) => script_mod.register_setup(sched),
     }
   }
   pub fn register_update(&self, sched: &mut Schedule) {
-    if let &GameModuleDescriptor::Native(native_mod) = &self {
-      (native_mod.register_update)(sched);
+    match self {
+      GameModule::Native(native_mod) => (native_mod.register_update)(sched),
+      GameModule::Script(script_mod) => script_mod.register_update(sched),
     }
   }
 }
@@ -70,5 +176,109 @@ pub struct NativeGameModule {
   pub register_update: fn(sched: &mut Schedule) -> (),
 }
 
-#[derive(Clone, PartialEq)]
-pub struct ScriptGameModule;
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn module(id: &str, depends_on: &[&str]) -> GameModuleDescriptor {
+    GameModuleDescriptor {
+      info: ModuleInfo {
+        id: id.to_owned(),
+        version: "0.1.0".to_owned(),
+        depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+      },
+      module: GameModule::Native(NativeGameModule {
+        register_init: |_| {},
+        register_setup: |_| {},
+        register_update: |_| {},
+      }),
+    }
+  }
+
+  fn resolve(modules: Vec<GameModuleDescriptor>) -> (Vec<String>, Vec<(String, ModError)>) {
+    let mut mgr = ModManager::default();
+    for module in modules {
+      mgr.register(module);
+    }
+    let (order, errors) = mgr.resolve();
+    (order.into_iter().map(|m| m.info.id.clone()).collect(), errors)
+  }
+
+  fn error(id: &str, error: ModError) -> (String, ModError) {
+    (id.to_owned(), error)
+  }
+
+  #[test]
+  fn dependencies_load_first() {
+    let (order, errors) = resolve(vec![
+      module("c", &["b"]),
+      module("b", &["a"]),
+      module("a", &[]),
+      module("d", &[]),
+    ]);
+    // modules without pending dependencies keep the registration order
+    assert_eq!(order, ["a", "d", "b", "c"]);
+    assert!(errors.is_empty());
+  }
+
+  #[test]
+  fn cycles_are_left_out() {
+    let (order, errors) = resolve(vec![
+      module("a", &["b"]),
+      module("b", &["a"]),
+      module("c", &[]),
+    ]);
+    assert_eq!(order, ["c"]);
+    assert_eq!(
+      errors,
+      [error("a", ModError::Cycle), error("b", ModError::Cycle)]
+    );
+  }
+
+  #[test]
+  fn missing_dependency_is_reported() {
+    let (order, errors) = resolve(vec![module("a", &["nope"]), module("b", &[])]);
+    assert_eq!(order, ["b"]);
+    assert_eq!(
+      errors,
+      [error("a", ModError::MissingDependency("nope".to_owned()))]
+    );
+  }
+
+  #[test]
+  fn failed_dependency_cascades_to_dependents() {
+    let (order, errors) = resolve(vec![
+      module("c", &["b"]),
+      module("b", &["a"]),
+      module("a", &["nope"]),
+    ]);
+    assert!(order.is_empty());
+    assert_eq!(
+      errors,
+      [
+        error("a", ModError::MissingDependency("nope".to_owned())),
+        error("b", ModError::DependencyFailed("a".to_owned())),
+        error("c", ModError::DependencyFailed("b".to_owned())),
+      ]
+    );
+  }
+
+  #[test]
+  fn duplicate_ids_keep_the_first_module() {
+    let mut first = module("a", &[]);
+    first.info.version = "1.0.0".to_owned();
+    let mut mgr = ModManager::default();
+    mgr
+      .register(first)
+      .register(module("a", &[]))
+      .register(module("b", &["a"]));
+    let (order, errors) = mgr.resolve();
+
+    let order: Vec<_> = order
+      .into_iter()
+      .map(|m| (m.info.id.as_str(), m.info.version.as_str()))
+      .collect();
+    assert_eq!(order, [("a", "1.0.0"), ("b", "0.1.0")]);
+    assert_eq!(errors, [error("a", ModError::Duplicate("a".to_owned()))]);
+  }
+}
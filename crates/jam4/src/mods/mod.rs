@@ -1,7 +1,11 @@
 use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+pub use discovery::*;
 pub use manager::*;
+pub use script::*;
 
+mod discovery;
 mod manager;
+mod script;
 
 /// one-off schedule, run during loading phase
 /// note the player camera is not yet created during loading phase
@@ -25,6 +29,9 @@ pub fn register_mods(world: &mut World) {
   let update_sched;
   {
     let mgr = world.resource::<ModManager>();
+    for (id, e) in mgr.resolve().1 {
+      error!("module {id} will not be loaded: {e}");
+    }
     init_sched = mgr.build_init_schedule(ModInit);
     setup_sched = mgr.build_setup_schedule(ModSetup);
     update_sched = mgr.build_update_schedule(ModUpdate);
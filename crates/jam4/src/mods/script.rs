@@ -0,0 +1,393 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bevy::prelude::*;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Scope, AST, INT};
+use thiserror::Error;
+
+use crate::{
+  boid::{Boid, TamedBoid},
+  level::{LevelFile, LevelId, LevelRegistry},
+};
+
+/// Game module written in Rhai, loaded at runtime
+///
+/// The script can define any of these functions, each gets a `Context`:
+/// - `init(ctx)` during `ModInit`, register levels here
+/// - `setup(ctx)` during `ModSetup`, after a level has been spawned
+/// - `update(ctx)` during `ModUpdate`, every frame while simulating
+///
+/// ```rhai
+/// fn init(ctx) {
+///   ctx.register_level(#{
+///     id: "scripted", // short for "<module>:scripted"
+///     name: "Scripted",
+///     bounds: #{ Subtract: [#{ Box: [3000.0, 3000.0] }, #{ Box: [2000.0, 2000.0] }] },
+///     finish_bounds: [0.0, 2500.0, 500.0, 500.0],
+///     starting_point: [0.0, -1500.0],
+///     spawn_points: [[0.0, 0.0]],
+///     boids_per_spawn_point: 20,
+///     next_level: "base_game:level1",
+///   });
+///   ctx.set_start_level("scripted");
+/// }
+///
+/// fn update(ctx) {
+///   for boid in ctx.boids() {
+///     if !boid.tamed { ctx.set_speed(boid.id, 200.0); }
+///   }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ScriptGameModule {
+  pub name: String,
+  ast: Arc<AST>,
+}
+
+impl PartialEq for ScriptGameModule {
+  fn eq(&self, other: &Self) -> bool {
+    self.name == other.name && Arc::ptr_eq(&self.ast, &other.ast)
+  }
+}
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+  #[error("could not compile script {0}: {1}")]
+  Compile(String, rhai::ParseError),
+}
+
+impl ScriptGameModule {
+  /// Compiled with the engine the script later runs on, so both agree on what is valid
+  pub fn compile(
+    engine: &ScriptEngine,
+    name: impl Into<String>,
+    source: &str,
+  ) -> Result<Self, ScriptError> {
+    let name = name.into();
+    match engine.0.compile(source) {
+      Ok(ast) => Ok(Self {
+        name,
+        ast: Arc::new(ast),
+      }),
+      Err(e) => Err(ScriptError::Compile(name, e)),
+    }
+  }
+
+  pub fn register_init(&self, sched: &mut Schedule) {
+    let module = self.clone();
+    sched.add_systems(move |world: &mut World| module.run_init(world));
+  }
+
+  pub fn register_setup(&self, sched: &mut Schedule) {
+    let module = self.clone();
+    sched.add_systems(move |world: &mut World| module.run_boid_phase(world, "setup"));
+  }
+
+  pub fn register_update(&self, sched: &mut Schedule) {
+    let module = self.clone();
+    sched.add_systems(move |world: &mut World| module.run_boid_phase(world, "update"));
+  }
+
+  fn has_fn(&self, name: &str) -> bool {
+    self.ast.iter_functions().any(|f| f.name == name)
+  }
+
+  fn call(&self, world: &World, name: &str, args: impl FuncArgs) {
+    let engine = &world.resource::<ScriptEngine>().0;
+    if let Err(e) = engine.call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args) {
+      error!("script {} failed in {name}: {e}", self.name);
+    }
+  }
+
+  fn run_init(&self, world: &mut World) {
+    if !self.has_fn("init") {
+      return;
+    }
+    let ctx = ScriptContext::default();
+    ctx.lock().module = self.name.clone();
+    self.call(world, "init", (ctx.clone(),));
+
+    let data = std::mem::take(&mut *ctx.lock());
+    for file in data.levels {
+      let unique = file.id.to_string();
+      let import_path = format!(
+        "smud::script_{}",
+        unique.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+      );
+      let mut shaders = world.resource_mut::<Assets<Shader>>();
+      let sdf = shaders.add(file.bounds_shape().shader(&import_path));
+      let obstacle_sdfs = file
+        .obstacles
+        .iter()
+        .enumerate()
+        .map(|(i, obstacle)| {
+          shaders.add(obstacle.shape.shader(&format!("{import_path}_obstacle{i}")))
+        })
+        .collect();
+      let fill = world
+        .resource::<AssetServer>()
+        .load(file.terrain.fill.clone());
+      let id = file.id.clone();
+      let level = file.into_level(sdf, fill, obstacle_sdfs);
+      if let Err(e) = world.resource_mut::<LevelRegistry>().register(id, level) {
+        error!("script {} could not register a level: {e}", self.name);
+      }
+    }
+    if let Some(start) = data.start_level {
+      world.resource_mut::<LevelRegistry>().start_level = Some(start);
+    }
+  }
+
+  /// setup and update both see the boids and can steer them
+  fn run_boid_phase(&self, world: &mut World, name: &str) {
+    if !self.has_fn(name) {
+      return;
+    }
+    let ctx = ScriptContext::default();
+    {
+      let delta = world.resource::<Time>().delta_seconds_f64();
+      let mut qry = world.query::<(Entity, &Transform, &Boid, Has<TamedBoid>)>();
+      let boids = qry
+        .iter(world)
+        .map(|(e, t, b, tamed)| {
+          let mut boid = Map::new();
+          boid.insert("id".into(), (e.to_bits() as INT).into());
+          boid.insert("x".into(), (t.translation.x as f64).into());
+          boid.insert("y".into(), (t.translation.y as f64).into());
+          boid.insert("dir_x".into(), (b.direction.x as f64).into());
+          boid.insert("dir_y".into(), (b.direction.y as f64).into());
+          boid.insert("speed".into(), (b.speed as f64).into());
+          boid.insert("tamed".into(), tamed.into());
+          boid.insert("player".into(), b.is_player.into());
+          boid.into()
+        })
+        .collect();
+      let mut data = ctx.lock();
+      data.delta = delta;
+      data.boids = boids;
+    }
+    self.call(world, name, (ctx.clone(),));
+
+    let data = std::mem::take(&mut *ctx.lock());
+    for (e, direction) in data.directions {
+      if let Some(mut boid) = world.get_mut::<Boid>(e) {
+        boid.direction = direction;
+      }
+    }
+    for (e, speed) in data.speeds {
+      if let Some(mut boid) = world.get_mut::<Boid>(e) {
+        boid.speed = speed;
+      }
+    }
+  }
+}
+
+/// Shared engine for all script modules, holds the `Context` API
+/// the mod loader keeps a clone to compile scripts with
+#[derive(Resource, Clone)]
+pub struct ScriptEngine(pub Arc<Engine>);
+
+impl Default for ScriptEngine {
+  fn default() -> Self {
+    let mut engine = Engine::new();
+    engine
+      .on_print(|s| info!("{s}"))
+      .register_type_with_name::<ScriptContext>("Context")
+      .register_get("delta", |ctx: &mut ScriptContext| ctx.lock().delta)
+      .register_fn("boids", |ctx: &mut ScriptContext| ctx.lock().boids.clone())
+      .register_fn("register_level", ScriptContext::register_level)
+      .register_fn("set_start_level", ScriptContext::set_start_level)
+      .register_fn("set_direction", ScriptContext::set_direction)
+      .register_fn("set_speed", ScriptContext::set_speed);
+    Self(Arc::new(engine))
+  }
+}
+
+#[derive(Default)]
+struct ContextData {
+  /// namespace for level ids given without a module
+  module: String,
+  delta: f64,
+  boids: Array,
+  levels: Vec<LevelFile>,
+  start_level: Option<LevelId>,
+  directions: Vec<(Entity, Vec2)>,
+  speeds: Vec<(Entity, f32)>,
+}
+
+/// Handle passed to script functions, changes are applied after the script returns
+#[derive(Clone, Default)]
+pub struct ScriptContext(Arc<Mutex<ContextData>>);
+
+impl ScriptContext {
+  fn lock(&self) -> MutexGuard<'_, ContextData> {
+    self.0.lock().unwrap()
+  }
+
+  fn register_level(&mut self, mut level: Map) -> Result<(), Box<EvalAltResult>> {
+    for key in ["id", "next_level"] {
+      if let Some(path) = level.get(key).and_then(|v| v.clone().into_string().ok()) {
+        let id = self.resolve(&path)?;
+        level.insert(key.into(), id.to_string().into());
+      }
+    }
+    let file: LevelFile = rhai::serde::from_dynamic(&level.into())?;
+    file.validate().map_err(|e| e.to_string())?;
+    self.lock().levels.push(file);
+    Ok(())
+  }
+
+  fn set_start_level(&mut self, path: &str) -> Result<(), Box<EvalAltResult>> {
+    let id = self.resolve(path)?;
+    self.lock().start_level = Some(id);
+    Ok(())
+  }
+
+  fn resolve(&self, path: &str) -> Result<LevelId, Box<EvalAltResult>> {
+    let module = self.lock().module.clone();
+    LevelId::resolve(path, &module).map_err(|e| e.to_string().into())
+  }
+
+  fn set_direction(&mut self, id: INT, x: f64, y: f64) {
+    let direction = Vec2::new(x as f32, y as f32).normalize_or_zero();
+    if direction != Vec2::ZERO {
+      self
+        .lock()
+        .directions
+        .push((Entity::from_bits(id as u64), direction));
+    }
+  }
+
+  fn set_speed(&mut self, id: INT, speed: f64) {
+    self
+      .lock()
+      .speeds
+      .push((Entity::from_bits(id as u64), speed as f32));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const INIT: &str = r#"
+    fn init(ctx) {
+      ctx.register_level(#{
+        id: "scripted",
+        name: "Scripted",
+        bounds: #{ Subtract: [#{ Box: [3000.0, 3000.0] }, #{ Box: [2000.0, 2000.0] }] },
+        finish_bounds: [0.0, 2500.0, 500.0, 500.0],
+        starting_point: [0.0, -1500.0],
+        spawn_points: [[0.0, 0.0]],
+        boids_per_spawn_point: 20,
+        next_level: "base_game:level1",
+      });
+      ctx.set_start_level("scripted");
+    }
+  "#;
+
+  fn context(module: &str) -> ScriptContext {
+    let ctx = ScriptContext::default();
+    ctx.lock().module = module.into();
+    ctx
+  }
+
+  fn boid() -> Boid {
+    Boid {
+      direction: Vec2::Y,
+      vision: 200.,
+      personal_space: 50.,
+      is_player: false,
+      speed: 100.,
+      turning_speed: 4.,
+    }
+  }
+
+  #[test]
+  fn compile_error_names_the_script() {
+    let result = ScriptGameModule::compile(&ScriptEngine::default(), "broken", "fn init(ctx) {");
+    assert!(matches!(result, Err(ScriptError::Compile(name, _)) if name == "broken"));
+  }
+
+  #[test]
+  fn register_level_produces_a_level_file() {
+    let engine = ScriptEngine::default();
+    let module = ScriptGameModule::compile(&engine, "test", INIT).unwrap();
+    let ctx = context("test");
+    engine
+      .0
+      .call_fn::<Dynamic>(&mut Scope::new(), &module.ast, "init", (ctx.clone(),))
+      .unwrap();
+
+    let data = ctx.lock();
+    assert_eq!(data.levels.len(), 1);
+    let level = &data.levels[0];
+    assert_eq!(level.id.to_string(), "test:scripted");
+    assert_eq!(level.name, "Scripted");
+    assert_eq!(level.spawn_points, vec![Vec2::ZERO]);
+    assert_eq!(
+      level
+        .next_level
+        .as_ref()
+        .map(ToString::to_string)
+        .as_deref(),
+      Some("base_game:level1")
+    );
+    assert_eq!(
+      data
+        .start_level
+        .as_ref()
+        .map(ToString::to_string)
+        .as_deref(),
+      Some("test:scripted")
+    );
+  }
+
+  #[test]
+  fn paths_resolve_against_the_module() {
+    let ctx = context("test");
+    assert_eq!(
+      ctx.resolve("scripted").unwrap().to_string(),
+      "test:scripted"
+    );
+    assert_eq!(
+      ctx.resolve("base_game:level2").unwrap().to_string(),
+      "base_game:level2"
+    );
+    assert!(ctx.resolve("bad:path:here").is_err());
+  }
+
+  #[test]
+  fn update_steers_untamed_boids() {
+    let engine = ScriptEngine::default();
+    let module = ScriptGameModule::compile(
+      &engine,
+      "test",
+      r#"
+        fn update(ctx) {
+          for boid in ctx.boids() {
+            if !boid.tamed {
+              ctx.set_speed(boid.id, 200.0);
+              ctx.set_direction(boid.id, 2.0, 0.0);
+            }
+          }
+        }
+      "#,
+    )
+    .unwrap();
+
+    let mut world = World::new();
+    world.insert_resource(Time::default());
+    world.insert_resource(engine);
+    let wild = world.spawn((Transform::default(), boid())).id();
+    let tamed = world.spawn((Transform::default(), boid(), TamedBoid)).id();
+
+    module.run_boid_phase(&mut world, "update");
+
+    let wild = world.get::<Boid>(wild).unwrap();
+    assert_eq!(wild.speed, 200.);
+    assert_eq!(wild.direction, Vec2::X);
+    let tamed = world.get::<Boid>(tamed).unwrap();
+    assert_eq!(tamed.speed, 100.);
+    assert_eq!(tamed.direction, Vec2::Y);
+  }
+}
@@ -6,15 +6,82 @@ use sdfu::{
   util::{CentralDifferenceEstimator, EstimateNormal},
   Dim2D, SDF,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::obstacle::{MovingObstacle, Obstacle};
+
+/// Distance at which sphere tracing counts as touching a wall
+const TRACE_HIT: f32 = 0.01;
+/// Sphere tracing steps before giving up on the path and only checking where it ends
+const TRACE_STEPS: usize = 1024;
+/// Deflected moves continue this far from the wall they hit
+const WALL_GAP: f32 = 1.0;
 
 #[derive(Component, Default)]
 pub struct Moveable {
   pub velocity: Vec3,
+  pub collision: CollisionResponse,
+}
+
+/// What a moveable does when it runs into the bounds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollisionResponse {
+  /// stops at the wall and gets `CollidedWithBounds`, which is the end of boids and the player
+  #[default]
+  Stop,
+  /// bounces off the wall, mirrored around its normal
+  Reflect,
+  /// keeps going along the wall, without the part of the move that goes into it
+  Slide,
 }
 
 #[derive(Component, Default)]
 pub struct CollidedWithBounds;
 
+/// Transforms after the last two fixed steps, the `Transform` that gets drawn is blended between
+/// them while the simulation only ever sees the latest one
+#[derive(Component)]
+pub struct InterpolatedTransform {
+  previous: Transform,
+  current: Transform,
+}
+
+/// Obstacles moving with the level time
+#[derive(Clone)]
+pub struct Obstacles {
+  list: Arc<[MovingObstacle]>,
+  /// level time the obstacles are evaluated at
+  time: f32,
+}
+
+impl Obstacles {
+  fn distance(&self, p: Vec2) -> f32 {
+    self
+      .list
+      .iter()
+      .map(|o| o.distance(p, self.time))
+      .fold(f32::INFINITY, f32::min)
+  }
+
+  fn normal(&self, p: Vec2) -> Vec2 {
+    let e = 0.1;
+    Vec2::new(
+      self.distance(p + Vec2::X * e) - self.distance(p - Vec2::X * e),
+      self.distance(p + Vec2::Y * e) - self.distance(p - Vec2::Y * e),
+    )
+    .normalize_or_zero()
+  }
+}
+
+impl Default for Obstacles {
+  fn default() -> Self {
+    Self {
+      list: Arc::new([]),
+      time: 0.0,
+    }
+  }
+}
+
 #[derive(Clone)]
 pub struct SdfBounds {
   sdf: Arc<dyn SDF<f32, Vec2> + Send + Sync>,
@@ -26,6 +93,95 @@ pub struct SdfBounds {
       CentralDifferenceEstimator<f32, Vec2, Dim2D>,
     >,
   >,
+  obstacles: Obstacles,
+}
+
+/// Area and resolution to sample the bounds at, see `MoveableBounds::bake`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BakeSettings {
+  /// corners of the sampled area, should cover everything that can move
+  pub min: Vec2,
+  pub max: Vec2,
+  pub cell_size: f32,
+}
+
+/// Signed distances sampled on a grid and bilinearly interpolated
+///
+/// sdfs are 1-Lipschitz, so interpolated distances are never off by more than half a cell diagonal
+/// and are exact along straight walls
+pub struct DistanceGrid {
+  min: Vec2,
+  cell_size: f32,
+  columns: usize,
+  rows: usize,
+  samples: Vec<f32>,
+}
+
+impl DistanceGrid {
+  pub fn bake(sdf: &dyn SDF<f32, Vec2>, settings: &BakeSettings) -> Self {
+    let size = ((settings.max - settings.min) / settings.cell_size)
+      .ceil()
+      .max(Vec2::ONE)
+      .as_uvec2()
+      + 1;
+    let (columns, rows) = (size.x as usize, size.y as usize);
+    let samples = (0..rows)
+      .flat_map(|y| (0..columns).map(move |x| (x, y)))
+      .map(|(x, y)| sdf.dist(settings.min + Vec2::new(x as f32, y as f32) * settings.cell_size))
+      .collect();
+    Self {
+      min: settings.min,
+      cell_size: settings.cell_size,
+      columns,
+      rows,
+      samples,
+    }
+  }
+
+  fn max(&self) -> Vec2 {
+    self.min + Vec2::new((self.columns - 1) as f32, (self.rows - 1) as f32) * self.cell_size
+  }
+
+  /// samples at the corners of the cell around `p` and where `p` is inside that cell,
+  /// points outside the grid use the nearest cell on its edge
+  fn cell(&self, p: Vec2) -> ([f32; 4], Vec2) {
+    let g = (p.clamp(self.min, self.max()) - self.min) / self.cell_size;
+    let x = (g.x as usize).min(self.columns - 2);
+    let y = (g.y as usize).min(self.rows - 2);
+    let i = y * self.columns + x;
+    let corners = [
+      self.samples[i],
+      self.samples[i + 1],
+      self.samples[i + self.columns],
+      self.samples[i + self.columns + 1],
+    ];
+    (corners, g - Vec2::new(x as f32, y as f32))
+  }
+
+  pub fn distance(&self, p: Vec2) -> f32 {
+    let ([d00, d10, d01, d11], f) = self.cell(p);
+    let d = (d00 + (d10 - d00) * f.x) * (1.0 - f.y) + (d01 + (d11 - d01) * f.x) * f.y;
+    // outside the grid the sdf is only known to be within this much of the edge sample,
+    // assume the closest a wall could be
+    d - p.distance(p.clamp(self.min, self.max()))
+  }
+
+  /// gradient of the interpolated distance
+  pub fn normal(&self, p: Vec2) -> Vec2 {
+    let ([d00, d10, d01, d11], f) = self.cell(p);
+    Vec2::new(
+      (d10 - d00) * (1.0 - f.y) + (d11 - d01) * f.y,
+      (d01 - d00) * (1.0 - f.x) + (d11 - d10) * f.x,
+    )
+    .normalize_or_zero()
+  }
+}
+
+/// Bounds answered from a `DistanceGrid`, cheaper than evaluating a complex sdf
+#[derive(Clone)]
+pub struct BakedBounds {
+  grid: Arc<DistanceGrid>,
+  obstacles: Obstacles,
 }
 
 #[derive(Resource, Default, Clone)]
@@ -33,6 +189,7 @@ pub enum MoveableBounds {
   #[default]
   None,
   Sdf(SdfBounds),
+  Baked(BakedBounds),
 }
 
 impl MoveableBounds {
@@ -41,43 +198,126 @@ impl MoveableBounds {
     Self::Sdf(SdfBounds {
       sdf: a.clone(),
       normals: Arc::new(estimate_normals(a, 0.001f32)),
+      obstacles: Obstacles::default(),
     })
   }
+
+  /// Samples the sdf into a grid, moving obstacles are still evaluated exactly
+  pub fn bake(&self, settings: &BakeSettings) -> Self {
+    match self {
+      MoveableBounds::Sdf(sdf) => MoveableBounds::Baked(BakedBounds {
+        grid: Arc::new(DistanceGrid::bake(sdf.sdf.as_ref(), settings)),
+        obstacles: sdf.obstacles.clone(),
+      }),
+      _ => self.clone(),
+    }
+  }
+
+  fn moving(&self) -> Option<&Obstacles> {
+    match self {
+      MoveableBounds::None => None,
+      MoveableBounds::Sdf(sdf) => Some(&sdf.obstacles),
+      MoveableBounds::Baked(baked) => Some(&baked.obstacles),
+    }
+  }
+
+  /// Adds obstacles that move with the level time, ignored without an sdf
+  pub fn with_obstacles(mut self, obstacles: Vec<MovingObstacle>) -> Self {
+    match &mut self {
+      MoveableBounds::None => {}
+      MoveableBounds::Sdf(SdfBounds { obstacles: o, .. })
+      | MoveableBounds::Baked(BakedBounds { obstacles: o, .. }) => o.list = obstacles.into(),
+    }
+    self
+  }
+
+  pub fn obstacles(&self) -> &[MovingObstacle] {
+    self.moving().map_or(&[], |o| &o.list)
+  }
+
+  /// Level time in seconds, moves the obstacles
+  pub fn set_time(&mut self, time: f32) {
+    match self {
+      MoveableBounds::None => {}
+      MoveableBounds::Sdf(SdfBounds { obstacles, .. })
+      | MoveableBounds::Baked(BakedBounds { obstacles, .. }) => obstacles.time = time,
+    }
+  }
+
   pub fn distance_to_edge(&self, p: Vec2) -> f32 {
     match self {
       MoveableBounds::None => f32::NEG_INFINITY,
-      MoveableBounds::Sdf(sdf) => sdf.sdf.dist(p),
+      MoveableBounds::Sdf(sdf) => sdf.sdf.dist(p).min(sdf.obstacles.distance(p)),
+      MoveableBounds::Baked(baked) => baked.grid.distance(p).min(baked.obstacles.distance(p)),
     }
   }
   pub fn edge_normal(&self, p: Vec2) -> Vec2 {
     match self {
       MoveableBounds::None => Vec2::ZERO,
+      MoveableBounds::Sdf(sdf) if sdf.obstacles.distance(p) < sdf.sdf.dist(p) => {
+        sdf.obstacles.normal(p)
+      }
       MoveableBounds::Sdf(sdf) => sdf.normals.normal_at(p),
+      MoveableBounds::Baked(baked) if baked.obstacles.distance(p) < baked.grid.distance(p) => {
+        baked.obstacles.normal(p)
+      }
+      MoveableBounds::Baked(baked) => baked.grid.normal(p),
     }
   }
 
-  pub fn bounce(&self, o: Vec2, p: Vec2) -> (Vec2, Vec2) {
-    let op = o + p;
-    match self {
-      MoveableBounds::None => (op, p),
-      MoveableBounds::Sdf(sdf) => {
-        let d = self.distance_to_edge(op);
-        if d > 0.0 {
-          (op, p)
-        } else if true {
-          (o, p)
-        } else {
-          let incident = p.normalize();
-          let normal = sdf.normals.normal_at(op);
-          let angle = (90.0f32.to_radians() - normal.angle_between(incident)) * 2.0;
-          let newp = Quat::from_rotation_z(angle).mul_vec3(p.extend(0.)).xy();
-          if sdf.sdf.dist(o + newp) < 0. {
-            return (o, p);
-          }
-          (o + newp, newp)
-        }
+  /// Sphere traces from `o` along `p`, returns how far along it the first wall is
+  /// steps never go further than the nearest wall, so thin walls can't be skipped
+  /// baked bounds only know walls as thin as their cells though
+  fn first_hit(&self, o: Vec2, p: Vec2) -> Option<f32> {
+    let length = p.length();
+    let direction = p / length;
+    let mut t = 0.0;
+    for _ in 0..TRACE_STEPS {
+      let d = self.distance_to_edge(o + direction * t);
+      if d <= TRACE_HIT {
+        return Some(t);
+      }
+      t += d;
+      if t >= length {
+        return None;
       }
     }
+    // grazing along a wall, settle for where the move ends
+    (self.distance_to_edge(o + p) <= TRACE_HIT).then_some(length)
+  }
+
+  /// Moves from `o` by `p`, returns where it ends up and the new direction of travel
+  /// or `None` when the wall stops it
+  /// reflected and slid moves carry on from the wall with what is left of `p`,
+  /// if that runs into a wall too it waits by the wall until it turns away
+  pub fn bounce(&self, o: Vec2, p: Vec2, response: CollisionResponse) -> Option<(Vec2, Vec2)> {
+    if let MoveableBounds::None = self {
+      return Some((o + p, p));
+    }
+    if p == Vec2::ZERO {
+      return Some((o, p));
+    }
+    let Some(t) = self.first_hit(o, p) else {
+      return Some((o + p, p));
+    };
+    let direction = p.normalize();
+    let normal = self.edge_normal(o + direction * t);
+    let rest = p - direction * t;
+    let into_wall = rest.dot(normal).min(0.0);
+    let newp = match response {
+      CollisionResponse::Stop => return None,
+      CollisionResponse::Reflect => rest - 2.0 * into_wall * normal,
+      CollisionResponse::Slide => rest - into_wall * normal,
+    };
+    let start = o + direction * (t - WALL_GAP).max(0.0);
+    // sliding head on into a wall leaves nothing to move along
+    if newp.length_squared() <= rest.length_squared() * 1e-6 {
+      return Some((start, p));
+    }
+    match self.first_hit(start, newp) {
+      None => Some((start + newp, newp)),
+      Some(_) => Some((start, newp)),
+    }
   }
 }
 
@@ -90,15 +330,207 @@ pub fn move_moveables(
   for (e, mut trn, mut mov) in qry.iter_mut() {
     // calculate next position
     let travel = mov.velocity * time.delta_seconds();
-    let (new_translation, new_v) = bounds.bounce(trn.translation.xy(), travel.xy());
-
-    if new_translation == trn.translation.xy() && travel.length_squared() > 0.0 {
-      // collided
+    let Some((new_translation, new_v)) =
+      bounds.bounce(trn.translation.xy(), travel.xy(), mov.collision)
+    else {
       cmd.entity(e).insert(CollidedWithBounds);
-    }
+      continue;
+    };
 
     // update position
     *trn = trn.with_translation(new_translation.extend(0.0));
-    mov.velocity = mov.velocity.length() * new_v.extend(0.0).normalize();
+    if let Some(direction) = new_v.try_normalize() {
+      mov.velocity = mov.velocity.length() * direction.extend(0.0);
+    }
+  }
+}
+
+/// Puts things back where the last fixed step left them, undoing `interpolate_transforms`
+pub fn restore_fixed_transforms(mut qry: Query<(&mut Transform, &InterpolatedTransform)>) {
+  for (mut trn, fixed) in qry.iter_mut() {
+    *trn = fixed.current;
+  }
+}
+
+pub fn record_fixed_transforms(
+  mut cmd: Commands,
+  mut qry: Query<
+    (Entity, &Transform, Option<&mut InterpolatedTransform>),
+    Or<(With<Moveable>, With<Obstacle>)>,
+  >,
+) {
+  for (e, trn, fixed) in qry.iter_mut() {
+    match fixed {
+      Some(mut fixed) => {
+        fixed.previous = fixed.current;
+        fixed.current = *trn;
+      }
+      None => {
+        cmd.entity(e).insert(InterpolatedTransform {
+          previous: *trn,
+          current: *trn,
+        });
+      }
+    }
+  }
+}
+
+/// Draws things part of the way to their next fixed step, by how much time is left over
+pub fn interpolate_transforms(
+  time: Res<Time<Fixed>>,
+  mut qry: Query<(&mut Transform, &InterpolatedTransform)>,
+) {
+  let s = time.overstep_percentage();
+  for (mut trn, fixed) in qry.iter_mut() {
+    trn.translation = fixed.previous.translation.lerp(fixed.current.translation, s);
+    trn.rotation = fixed.previous.rotation.slerp(fixed.current.rotation, s);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::f32::consts::FRAC_1_SQRT_2;
+
+  use super::*;
+  use crate::shape::Shape;
+
+  const CELL_SIZE: f32 = 25.0;
+
+  fn settings(half: f32) -> BakeSettings {
+    BakeSettings {
+      min: Vec2::splat(-half),
+      max: Vec2::splat(half),
+      cell_size: CELL_SIZE,
+    }
+  }
+
+  /// evenly spread points that don't line up with the grid
+  fn sample_points(half: f32) -> impl Iterator<Item = Vec2> {
+    (0..4096).map(move |i| {
+      let u = Vec2::new((i as f32 * 0.754_877_7).fract(), (i as f32 * 0.569_840_3).fract());
+      (u * 2.0 - 1.0) * half
+    })
+  }
+
+  fn compound_shape() -> Shape {
+    Shape::Box(Vec2::new(2500., 2000.)).subtract(
+      Shape::Box(Vec2::new(2000., 1500.)).subtract(Shape::Union(vec![
+        Shape::Circle(400.).translate(Vec2::new(-800., 300.)),
+        Shape::Box(Vec2::new(600., 100.)).translate(Vec2::new(700., -500.)),
+        Shape::Triangle(
+          Vec2::new(0., 800.),
+          Vec2::new(500., 1400.),
+          Vec2::new(900., 700.),
+        ),
+      ])),
+    )
+  }
+
+  #[test]
+  fn baked_distance_is_within_half_a_cell_diagonal() {
+    let analytic = MoveableBounds::from_sdf(compound_shape());
+    let baked = analytic.bake(&settings(3000.));
+    for p in sample_points(3000.) {
+      let (expected, actual) = (analytic.distance_to_edge(p), baked.distance_to_edge(p));
+      assert!(
+        (expected - actual).abs() <= CELL_SIZE * FRAC_1_SQRT_2 + 1e-3,
+        "distance at {p} is {actual}, expected {expected}"
+      );
+    }
+  }
+
+  #[test]
+  fn baked_distance_outside_the_grid_is_never_further_from_walls() {
+    let analytic = MoveableBounds::from_sdf(compound_shape());
+    let baked = analytic.bake(&settings(1000.));
+    for p in sample_points(3000.) {
+      let (expected, actual) = (analytic.distance_to_edge(p), baked.distance_to_edge(p));
+      assert!(
+        actual <= expected + CELL_SIZE * FRAC_1_SQRT_2 + 1e-3,
+        "distance at {p} is {actual}, expected at most {expected}"
+      );
+    }
+  }
+
+  #[test]
+  fn baked_normals_follow_the_analytic_gradient() {
+    let analytic = MoveableBounds::from_sdf(Shape::Circle(1000.));
+    let baked = analytic.bake(&settings(3000.));
+    // the gradient is undefined right at the walls and at the circle's center
+    let points = sample_points(3000.)
+      .filter(|p| analytic.distance_to_edge(*p).abs() > CELL_SIZE && p.length() > CELL_SIZE * 4.);
+    for p in points {
+      let (expected, actual) = (p.normalize(), baked.edge_normal(p));
+      assert!(
+        expected.dot(actual) > 0.99,
+        "normal at {p} is {actual}, expected {expected}"
+      );
+    }
+  }
+
+  fn wall_above() -> MoveableBounds {
+    MoveableBounds::from_sdf(Shape::Box(Vec2::new(1000., 100.)).translate(Vec2::new(0., 200.)))
+  }
+
+  #[test]
+  fn stopping_walls_stop() {
+    let bounds = wall_above();
+    let o = Vec2::new(0., 50.);
+    let free = bounds.bounce(o, Vec2::new(30., 20.), CollisionResponse::Stop);
+    assert_eq!(free, Some((Vec2::new(30., 70.), Vec2::new(30., 20.))));
+    let into_wall = bounds.bounce(o, Vec2::new(30., 100.), CollisionResponse::Stop);
+    assert_eq!(into_wall, None);
+  }
+
+  #[test]
+  fn reflecting_walls_mirror_the_rest_of_the_move() {
+    let (position, direction) = wall_above()
+      .bounce(Vec2::new(0., 50.), Vec2::new(30., 100.), CollisionResponse::Reflect)
+      .expect("reflected");
+    assert!(position.abs_diff_eq(Vec2::new(29.7, 49.), 0.5), "ended up at {position}");
+    assert!(direction.abs_diff_eq(Vec2::new(15., -50.), 0.5), "heading {direction}");
+  }
+
+  #[test]
+  fn sliding_walls_keep_the_rest_of_the_move_along_them() {
+    let bounds = wall_above();
+    let (position, direction) = bounds
+      .bounce(Vec2::new(0., 50.), Vec2::new(30., 100.), CollisionResponse::Slide)
+      .expect("slid");
+    assert!(position.abs_diff_eq(Vec2::new(29.7, 99.), 0.5), "ended up at {position}");
+    assert!(direction.abs_diff_eq(Vec2::new(15., 0.), 0.5), "heading {direction}");
+
+    let (position, direction) = bounds
+      .bounce(Vec2::new(0., 50.), Vec2::new(0., 100.), CollisionResponse::Slide)
+      .expect("slid head on");
+    assert!(position.abs_diff_eq(Vec2::new(0., 99.), 0.01), "ended up at {position}");
+    assert_eq!(direction, Vec2::new(0., 100.));
+  }
+
+  #[test]
+  fn fast_moves_never_pass_through_thin_walls() {
+    // one unit thick, too wide to go around
+    let bounds =
+      MoveableBounds::from_sdf(Shape::Box(Vec2::new(1e6, 0.5)).translate(Vec2::new(0., 100.)));
+    let responses = [CollisionResponse::Stop, CollisionResponse::Reflect, CollisionResponse::Slide];
+    for response in responses {
+      for speed in [300., 1000., 5000., 50000.] {
+        for dt in [1. / 240., 1. / 60., 1. / 10., 1.] {
+          let mut position = Vec2::ZERO;
+          let mut velocity = Vec2::new(0.3, 1.).normalize() * speed;
+          let mut stopped = false;
+          for _ in 0..(2. / dt) as usize {
+            let Some((next, direction)) = bounds.bounce(position, velocity * dt, response) else {
+              stopped = true;
+              break;
+            };
+            assert!(next.y < 99.5, "{response:?} at {speed} every {dt}s went through to {next}");
+            position = next;
+            velocity = direction.normalize() * speed;
+          }
+          assert_eq!(stopped, response == CollisionResponse::Stop, "{response:?} at {speed}");
+        }
+      }
+    }
   }
 }
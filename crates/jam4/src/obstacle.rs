@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+use bevy_smud::{Frame, SmudShape};
+use serde::{Deserialize, Serialize};
+
+use crate::{level::LevelManager, moveable::MoveableBounds, shape::Shape};
+
+/// How an obstacle moves, offsets and angles are relative to where it rests
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Motion {
+  /// swings along `amplitude` and rocks by up to `spin` radians, once every `period` seconds
+  Sine {
+    amplitude: Vec2,
+    #[serde(default)]
+    spin: f32,
+    period: f32,
+    /// fraction of a period
+    #[serde(default)]
+    phase: f32,
+  },
+  /// moves linearly from keyframe to keyframe, starting over after the last one
+  /// the first keyframe should be at 0 seconds
+  Keyframes(Vec<Keyframe>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe {
+  /// in seconds
+  pub time: f32,
+  pub offset: Vec2,
+  #[serde(default)]
+  pub angle: f32,
+}
+
+impl Motion {
+  /// offset and angle `time` seconds into the level
+  pub fn at(&self, time: f32) -> (Vec2, f32) {
+    match self {
+      Motion::Sine {
+        amplitude,
+        spin,
+        period,
+        phase,
+      } => {
+        let s = (std::f32::consts::TAU * (time / period + phase)).sin();
+        (*amplitude * s, *spin * s)
+      }
+      Motion::Keyframes(keys) => {
+        let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+          return (Vec2::ZERO, 0.0);
+        };
+        if last.time <= 0.0 {
+          return (first.offset, first.angle);
+        }
+        let t = time.rem_euclid(last.time);
+        let Some(i) = keys.iter().position(|k| k.time > t) else {
+          return (last.offset, last.angle);
+        };
+        if i == 0 {
+          return (first.offset, first.angle);
+        }
+        let (a, b) = (keys[i - 1], keys[i]);
+        let f = (t - a.time) / (b.time - a.time);
+        (a.offset.lerp(b.offset, f), a.angle + (b.angle - a.angle) * f)
+      }
+    }
+  }
+
+  pub fn validate(&self) -> Result<(), String> {
+    match self {
+      Motion::Sine { period, .. } if !(period.is_finite() && *period > 0.0) => {
+        Err("sine period must be a positive number of seconds".to_owned())
+      }
+      Motion::Keyframes(keys) if keys.is_empty() => Err("keyframes must not be empty".to_owned()),
+      Motion::Keyframes(keys) if keys.windows(2).any(|w| w[1].time <= w[0].time) => {
+        Err("keyframes must be in time order".to_owned())
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+/// Shape that moves during the level, collides like the rest of the bounds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MovingObstacle {
+  /// around the origin, which is also the pivot
+  pub shape: Shape,
+  pub position: Vec2,
+  pub motion: Motion,
+}
+
+impl MovingObstacle {
+  pub fn transform_at(&self, time: f32) -> Transform {
+    let (offset, angle) = self.motion.at(time);
+    Transform::from_translation((self.position + offset).extend(0.0))
+      .with_rotation(Quat::from_rotation_z(angle))
+  }
+
+  /// signed distance to the obstacle `time` seconds into the level, negative inside
+  pub fn distance(&self, p: Vec2, time: f32) -> f32 {
+    let (offset, angle) = self.motion.at(time);
+    let local = Mat2::from_angle(-angle).mul_vec2(p - self.position - offset);
+    self.shape.distance(local)
+  }
+
+  /// `sdf` is a shader generated from `shape`
+  pub fn smud_shape(&self, sdf: Handle<Shader>, fill: Handle<Shader>, color: Color) -> SmudShape {
+    SmudShape {
+      color,
+      sdf,
+      frame: Frame::Quad(self.shape.extent()),
+      fill,
+    }
+  }
+}
+
+/// Index of the obstacle in `MoveableBounds::obstacles` that the entity renders
+#[derive(Component)]
+pub struct Obstacle(pub usize);
+
+/// Moves the obstacles to where they are at the current level time
+pub fn animate_obstacles(
+  mut bounds: ResMut<MoveableBounds>,
+  lvl_mgr: Res<LevelManager>,
+  mut qry: Query<(&Obstacle, &mut Transform)>,
+) {
+  let time = lvl_mgr.watch.elapsed_secs();
+  bounds.set_time(time);
+  for (obstacle, mut transform) in qry.iter_mut() {
+    let Some(moving) = bounds.obstacles().get(obstacle.0) else {
+      continue;
+    };
+    let z = transform.translation.z;
+    *transform = moving.transform_at(time);
+    transform.translation.z = z;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(time: f32, x: f32, y: f32, angle: f32) -> Keyframe {
+    Keyframe {
+      time,
+      offset: Vec2::new(x, y),
+      angle,
+    }
+  }
+
+  fn assert_at(motion: &Motion, time: f32, offset: Vec2, angle: f32) {
+    let (actual_offset, actual_angle) = motion.at(time);
+    assert!(
+      actual_offset.abs_diff_eq(offset, 1e-4) && (actual_angle - angle).abs() < 1e-4,
+      "at {time}: expected {offset} {angle}, got {actual_offset} {actual_angle}"
+    );
+  }
+
+  fn path() -> Motion {
+    Motion::Keyframes(vec![
+      key(0., 0., 0., 0.),
+      key(2., 10., 0., 1.),
+      key(4., 10., 20., 1.),
+    ])
+  }
+
+  #[test]
+  fn keyframes_start_at_the_first_key() {
+    assert_at(&path(), 0., Vec2::ZERO, 0.);
+  }
+
+  #[test]
+  fn keyframes_interpolate_between_keys() {
+    assert_at(&path(), 1., Vec2::new(5., 0.), 0.5);
+    assert_at(&path(), 3., Vec2::new(10., 10.), 1.);
+  }
+
+  #[test]
+  fn keyframes_are_exact_on_a_key() {
+    assert_at(&path(), 2., Vec2::new(10., 0.), 1.);
+  }
+
+  #[test]
+  fn keyframes_loop_after_the_last_key() {
+    // the last key is where the loop starts over, it jumps back to the first key
+    assert_at(&path(), 4., Vec2::ZERO, 0.);
+    assert_at(&path(), 5., Vec2::new(5., 0.), 0.5);
+    assert_at(&path(), 4. * 10. + 3., Vec2::new(10., 10.), 1.);
+  }
+
+  #[test]
+  fn keyframes_back_to_the_start_ping_pong() {
+    let motion = Motion::Keyframes(vec![
+      key(0., 0., 0., 0.),
+      key(1., 10., 0., 0.),
+      key(2., 0., 0., 0.),
+    ]);
+    assert_at(&motion, 0.5, Vec2::new(5., 0.), 0.);
+    assert_at(&motion, 1.5, Vec2::new(5., 0.), 0.);
+    // no jump where the loop wraps around
+    assert_at(&motion, 1.9999, Vec2::new(0.001, 0.), 0.);
+    assert_at(&motion, 2.5, Vec2::new(5., 0.), 0.);
+  }
+
+  #[test]
+  fn sine_swings_back_and_forth_every_period() {
+    let motion = Motion::Sine {
+      amplitude: Vec2::new(10., 0.),
+      spin: 1.,
+      period: 2.,
+      phase: 0.,
+    };
+    assert_at(&motion, 0., Vec2::ZERO, 0.);
+    assert_at(&motion, 0.5, Vec2::new(10., 0.), 1.);
+    assert_at(&motion, 1.5, Vec2::new(-10., 0.), -1.);
+    assert_at(&motion, 2.5, Vec2::new(10., 0.), 1.);
+  }
+
+  #[test]
+  fn sine_phase_is_a_fraction_of_the_period() {
+    let motion = Motion::Sine {
+      amplitude: Vec2::new(0., 4.),
+      spin: 0.,
+      period: 8.,
+      phase: 0.25,
+    };
+    assert_at(&motion, 0., Vec2::new(0., 4.), 0.);
+    assert_at(&motion, 4., Vec2::new(0., -4.), 0.);
+  }
+}
@@ -1,9 +1,10 @@
 use bevy::{ecs::system::EntityCommands, prelude::*, sprite::Mesh2dHandle};
 use bevy_hanabi::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
   boid::{Boid, BoidConfig},
-  moveable::Moveable,
+  moveable::{CollisionResponse, Moveable},
 };
 
 #[derive(Resource)]
@@ -147,6 +148,14 @@ impl FromWorld for PlayerInfo {
 #[derive(Component, Default)]
 pub struct Player;
 
+/// Player actions for the current tick, written by the input layer or a replay
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInput {
+  /// -1.0 (right) to 1.0 (left)
+  pub turn: f32,
+  pub boost: bool,
+}
+
 #[derive(Bundle)]
 pub struct PlayerBundle {
   pub mesh: Mesh2dHandle,
@@ -168,9 +177,13 @@ pub fn spawn_player<'w, 's, 'a>(
   player: &PlayerInfo,
   spawn_point: Vec2,
   bconfig: &BoidConfig,
+  collision: CollisionResponse,
 ) -> EntityCommands<'w, 's, 'a> {
   cmd.spawn(PlayerBundle {
-    moveable: Moveable::default(),
+    moveable: Moveable {
+      collision,
+      ..default()
+    },
     boid: Boid {
       is_player: true,
       personal_space: 100.,
@@ -192,3 +205,30 @@ pub fn spawn_player<'w, 's, 'a>(
     player: Player,
   })
 }
+
+pub fn steer_player(
+  mut qry: Query<&mut Boid, With<Player>>,
+  input: Res<PlayerInput>,
+  mut player: ResMut<PlayerInfo>,
+  bconfig: Res<BoidConfig>,
+  time: Res<Time>,
+) {
+  let Ok(mut p) = qry.get_single_mut() else {
+    return;
+  };
+
+  if input.boost && !player.in_boost_mode {
+    player.in_boost_mode = true;
+    p.speed = bconfig.min_speed;
+    p.turning_speed = bconfig.min_turn_speed;
+  } else if !input.boost && player.in_boost_mode {
+    player.in_boost_mode = false;
+    p.speed = bconfig.max_speed;
+    p.turning_speed = bconfig.max_turn_speed;
+  }
+
+  let turning_force =
+    Mat2::from_angle(90.0f32.to_radians()).mul_vec2(p.direction) * input.turn.clamp(-1.0, 1.0);
+  p.direction =
+    (p.direction + (turning_force * time.delta_seconds() * p.turning_speed)).normalize();
+}
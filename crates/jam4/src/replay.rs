@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+  level::{LevelId, LevelManager},
+  PlayerInput,
+};
+
+/// Everything needed to reproduce a level run
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+  pub level: LevelId,
+  /// boids start in the level's fixed pattern if not set
+  #[serde(default)]
+  pub seed: Option<u64>,
+  pub timestep: Duration,
+  /// one entry per `FixedUpdate` tick
+  pub inputs: Vec<PlayerInput>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+  #[error("could not access replay file: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("could not parse replay file: {0}")]
+  Parse(#[from] ron::error::SpannedError),
+  #[error("could not serialize replay: {0}")]
+  Serialize(#[from] ron::Error),
+  #[error("no replay stored under {0}")]
+  Missing(String),
+  #[error("local storage is not available")]
+  NoStorage,
+}
+
+impl Replay {
+  /// Reads a replay file, on wasm `path` is a `localStorage` key
+  pub fn load(path: &str) -> Result<Self, ReplayError> {
+    Ok(ron::from_str(&read(path)?)?)
+  }
+
+  pub fn save(&self, path: &str) -> Result<(), ReplayError> {
+    write(path, &ron::to_string(self)?)
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(path: &str) -> Result<String, ReplayError> {
+  Ok(std::fs::read_to_string(path)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(path: &str, contents: &str) -> Result<(), ReplayError> {
+  Ok(std::fs::write(path, contents)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage() -> Result<web_sys::Storage, ReplayError> {
+  web_sys::window()
+    .and_then(|w| w.local_storage().ok().flatten())
+    .ok_or(ReplayError::NoStorage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(path: &str) -> Result<String, ReplayError> {
+  storage()?
+    .get_item(path)
+    .map_err(|_| ReplayError::NoStorage)?
+    .ok_or_else(|| ReplayError::Missing(path.to_owned()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(path: &str, contents: &str) -> Result<(), ReplayError> {
+  storage()?
+    .set_item(path, contents)
+    .map_err(|_| ReplayError::NoStorage)
+}
+
+#[derive(Event)]
+pub enum ReplayCommand {
+  /// write the current (or last) run to a file, or to `localStorage` on wasm
+  Save(String),
+  /// play back a run, the level is loaded on the next `StartGame` or `Retry`
+  Play(Replay),
+}
+
+/// Records every run and optionally feeds a previous run back as input
+#[derive(Resource, Default)]
+pub struct ReplayManager {
+  pub recording: Option<Replay>,
+  pub playback: Option<Replay>,
+  pending: Option<Replay>,
+  tick: usize,
+}
+
+impl ReplayManager {
+  pub fn is_playing(&self) -> bool {
+    self.playback.is_some()
+  }
+}
+
+pub fn process_replay_commands(
+  mut cmds: EventReader<ReplayCommand>,
+  mut replays: ResMut<ReplayManager>,
+  mut lvl_mgr: ResMut<LevelManager>,
+) {
+  for cmd in cmds.read() {
+    match cmd {
+      ReplayCommand::Save(path) => {
+        let Some(replay) = &replays.recording else {
+          warn!("nothing recorded yet, not saving replay to {path}");
+          continue;
+        };
+        match replay.save(path) {
+          Ok(()) => info!("saved replay to {path}"),
+          Err(e) => error!("failed to save replay to {path}: {e}"),
+        }
+      }
+      ReplayCommand::Play(replay) => {
+        lvl_mgr.current_level = Some(replay.level.clone());
+        lvl_mgr.level_complete = false;
+        lvl_mgr.next_seed = replay.seed;
+        replays.pending = Some(replay.clone());
+      }
+    }
+  }
+}
+
+pub fn start_recording(
+  mut replays: ResMut<ReplayManager>,
+  lvl_mgr: Res<LevelManager>,
+  time: Res<Time<Fixed>>,
+) {
+  let Some(level) = lvl_mgr.current_level.clone() else {
+    return;
+  };
+  // a replay only covers a single run, anything after that is live input again
+  replays.playback = replays.pending.take();
+  if let Some(playback) = &replays.playback {
+    if playback.timestep != time.timestep() {
+      warn!(
+        "replay was recorded with a {:?} timestep but running at {:?}, it will diverge",
+        playback.timestep,
+        time.timestep()
+      );
+    }
+  }
+  replays.tick = 0;
+  replays.recording = Some(Replay {
+    level,
+    seed: lvl_mgr.seed,
+    timestep: time.timestep(),
+    inputs: Vec::new(),
+  });
+}
+
+/// Runs at the start of every simulation tick
+pub fn record_player_input(mut replays: ResMut<ReplayManager>, mut input: ResMut<PlayerInput>) {
+  let replays = &mut *replays;
+  if let Some(playback) = &replays.playback {
+    *input = playback
+      .inputs
+      .get(replays.tick)
+      .copied()
+      .unwrap_or_default();
+  }
+  if let Some(recording) = &mut replays.recording {
+    recording.inputs.push(*input);
+  }
+  replays.tick += 1;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    boid::Boid,
+    tests::{corridor_level, headless_app, play, start_game, weave},
+    SimulationState,
+  };
+
+  /// Player and boid positions in spawn order
+  fn positions(app: &mut App) -> Vec<Vec3> {
+    let mut qry = app.world.query_filtered::<(Entity, &Transform), With<Boid>>();
+    let mut positions: Vec<_> = qry
+      .iter(&app.world)
+      .map(|(entity, transform)| (entity, transform.translation))
+      .collect();
+    positions.sort_by_key(|(entity, _)| *entity);
+    positions.into_iter().map(|(_, p)| p).collect()
+  }
+
+  fn record(seed: Option<u64>) -> (Replay, Vec<Vec3>) {
+    let mut app = headless_app(corridor_level());
+    app.world.resource_mut::<LevelManager>().next_seed = seed;
+    start_game(&mut app);
+    assert_eq!(play(&mut app, weave), SimulationState::LevelComplete);
+    let replay = app
+      .world
+      .resource::<ReplayManager>()
+      .recording
+      .clone()
+      .expect("run was recorded");
+    (replay, positions(&mut app))
+  }
+
+  fn play_back(replay: Replay) -> (SimulationState, Vec<Vec3>) {
+    let mut app = headless_app(corridor_level());
+    app.world.send_event(ReplayCommand::Play(replay));
+    start_game(&mut app);
+    // live input is ignored while the replay is playing
+    let state = play(&mut app, |_| PlayerInput {
+      turn: -1.,
+      boost: true,
+    });
+    (state, positions(&mut app))
+  }
+
+  fn assert_replay_matches(seed: Option<u64>) {
+    let (replay, recorded) = record(seed);
+    assert_eq!(replay.seed, seed);
+    let replay: Replay = ron::from_str(&ron::to_string(&replay).unwrap()).unwrap();
+    let (state, replayed) = play_back(replay);
+    assert_eq!(state, SimulationState::LevelComplete);
+    assert_eq!(replayed, recorded);
+  }
+
+  #[test]
+  fn replay_reproduces_run() {
+    assert_replay_matches(None);
+  }
+
+  #[test]
+  fn replay_reproduces_seeded_run() {
+    assert_replay_matches(Some(42));
+  }
+}
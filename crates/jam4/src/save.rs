@@ -0,0 +1,225 @@
+use std::{
+  collections::{HashMap, HashSet},
+  time::Duration,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+  boid::{Boid, TamedBoid},
+  level::{LevelId, LevelManager},
+  Player,
+};
+
+/// file on native, `localStorage` key on wasm
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_PATH: &str = "save.ron";
+#[cfg(target_arch = "wasm32")]
+const SAVE_PATH: &str = "triangle-apocalypse-save";
+
+/// Best results for a single level
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LevelRecord {
+  pub best_time: Option<Duration>,
+  pub most_rescued: u32,
+}
+
+/// Progress that survives restarts, stored in a file on native and in `localStorage` on wasm
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SaveData {
+  pub unlocked: HashSet<LevelId>,
+  pub records: HashMap<LevelId, LevelRecord>,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+  #[error("could not access save data: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("could not parse save data: {0}")]
+  Parse(#[from] ron::error::SpannedError),
+  #[error("could not serialize save data: {0}")]
+  Serialize(#[from] ron::Error),
+  #[error("local storage is not available")]
+  NoStorage,
+}
+
+impl SaveData {
+  /// Saved progress, empty if nothing has been saved yet
+  pub fn load() -> Result<Self, SaveError> {
+    Self::load_from(SAVE_PATH)
+  }
+
+  pub fn save(&self) -> Result<(), SaveError> {
+    self.save_to(SAVE_PATH)
+  }
+
+  pub fn load_from(path: &str) -> Result<Self, SaveError> {
+    match read(path)? {
+      Some(contents) => Ok(ron::from_str(&contents)?),
+      None => Ok(default()),
+    }
+  }
+
+  pub fn save_to(&self, path: &str) -> Result<(), SaveError> {
+    write(path, &ron::to_string(self)?)
+  }
+
+  /// Keeps the best time and rescue count independently
+  pub fn record(&mut self, level: LevelId, time: Duration, rescued: u32) {
+    let record = self.records.entry(level).or_default();
+    if !record.best_time.is_some_and(|best| best <= time) {
+      record.best_time = Some(time);
+    }
+    record.most_rescued = record.most_rescued.max(rescued);
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(path: &str) -> Result<Option<String>, SaveError> {
+  match std::fs::read_to_string(path) {
+    Ok(contents) => Ok(Some(contents)),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(e.into()),
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(path: &str, contents: &str) -> Result<(), SaveError> {
+  Ok(std::fs::write(path, contents)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage() -> Result<web_sys::Storage, SaveError> {
+  web_sys::window()
+    .and_then(|w| w.local_storage().ok().flatten())
+    .ok_or(SaveError::NoStorage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(path: &str) -> Result<Option<String>, SaveError> {
+  storage()?.get_item(path).map_err(|_| SaveError::NoStorage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(path: &str, contents: &str) -> Result<(), SaveError> {
+  storage()?
+    .set_item(path, contents)
+    .map_err(|_| SaveError::NoStorage)
+}
+
+pub fn record_level_result(
+  mut save: ResMut<SaveData>,
+  lvl_mgr: Res<LevelManager>,
+  qry_boid: Query<Entity, (With<Boid>, With<TamedBoid>, Without<Player>)>,
+) {
+  let Some(level_id) = lvl_mgr.current_level.clone() else {
+    return;
+  };
+  save.unlocked.extend(lvl_mgr.unlocked.iter().cloned());
+  save.record(
+    level_id,
+    lvl_mgr.watch.elapsed(),
+    qry_boid.iter().count() as u32,
+  );
+}
+
+/// Only the full game writes progress, headless runs keep it in memory
+pub fn persist_save_data(save: Res<SaveData>) {
+  if let Err(e) = save.save() {
+    error!("failed to save progress: {e}");
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    level::{LevelInfo, LevelRegistry},
+    tests::{corridor_id, corridor_level, headless_app, play, start_game, weave},
+    SimulationState,
+  };
+
+  fn level(name: &str) -> LevelId {
+    LevelId::new("test", name).unwrap()
+  }
+
+  /// unique per test so parallel tests don't share files
+  #[cfg(not(target_arch = "wasm32"))]
+  fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("jam4-{}-{name}.ron", std::process::id()));
+    path.to_string_lossy().into_owned()
+  }
+
+  #[test]
+  fn record_keeps_the_best_time_and_rescue_count() {
+    let mut save = SaveData::default();
+    save.record(level("a"), Duration::from_secs(60), 10);
+    save.record(level("a"), Duration::from_secs(50), 5);
+    save.record(level("a"), Duration::from_secs(70), 20);
+    assert_eq!(
+      save.records[&level("a")],
+      LevelRecord {
+        best_time: Some(Duration::from_secs(50)),
+        most_rescued: 20,
+      }
+    );
+    assert!(!save.records.contains_key(&level("b")));
+  }
+
+  #[test]
+  fn completing_a_level_unlocks_the_next_one() {
+    let next = level("next");
+    let mut app = headless_app(LevelInfo {
+      next_level: Some(next.clone()),
+      ..corridor_level()
+    });
+    app
+      .world
+      .resource_mut::<LevelRegistry>()
+      .register(next.clone(), corridor_level())
+      .unwrap();
+    start_game(&mut app);
+    assert_eq!(play(&mut app, weave), SimulationState::LevelComplete);
+
+    let save = app.world.resource::<SaveData>();
+    assert!(save.unlocked.contains(&corridor_id()));
+    assert!(save.unlocked.contains(&next));
+    assert!(save.records[&corridor_id()].best_time.is_some());
+    assert!(!save.records.contains_key(&next));
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  #[test]
+  fn save_data_round_trips() {
+    let path = temp_path("round-trip");
+    let mut save = SaveData::default();
+    save.unlocked.insert(level("a"));
+    save.record(level("a"), Duration::from_millis(61_500), 12);
+    save.save_to(&path).unwrap();
+
+    let loaded = SaveData::load_from(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unlocked, save.unlocked);
+    assert_eq!(loaded.records, save.records);
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  #[test]
+  fn missing_save_is_empty() {
+    let loaded = SaveData::load_from(&temp_path("missing")).unwrap();
+    assert!(loaded.unlocked.is_empty());
+    assert!(loaded.records.is_empty());
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  #[test]
+  fn corrupt_save_is_a_parse_error() {
+    let path = temp_path("corrupt");
+    std::fs::write(&path, "(unlocked: [\"test:a\"").unwrap();
+    let result = SaveData::load_from(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(SaveError::Parse(_))));
+  }
+}
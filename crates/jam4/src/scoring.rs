@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{level::LevelInfo, PlayerInfo};
+
+pub const POINTS_PER_RESCUE: u32 = 100;
+pub const POINTS_PER_SECOND_LEFT: u32 = 10;
+pub const PENALTY_PER_LOST_BOID: u32 = 50;
+pub const PENALTY_PER_BOOST_SECOND: u32 = 5;
+
+/// Minimum score for 1, 2 and 3 stars
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StarThresholds {
+  pub one: u32,
+  pub two: u32,
+  pub three: u32,
+}
+
+impl StarThresholds {
+  /// One star for meeting the rescue goal (or half the boids if there is none),
+  /// three for rescuing 90% of the boids
+  pub fn for_level(lvl: &LevelInfo) -> Self {
+    let total = lvl.spawn_points.len() as u32 * lvl.boids_per_spawn_point.max(0) as u32;
+    let goal = lvl.rescue_goal.map_or(total / 2, u32::from);
+    let one = goal * POINTS_PER_RESCUE;
+    let three = (total * POINTS_PER_RESCUE * 9 / 10).max(one);
+    Self {
+      one,
+      two: (one + three) / 2,
+      three,
+    }
+  }
+
+  pub fn stars(&self, score: u32) -> u8 {
+    match score {
+      s if s >= self.three => 3,
+      s if s >= self.two => 2,
+      s if s >= self.one => 1,
+      _ => 0,
+    }
+  }
+}
+
+/// Things that happened during the current run that affect the score
+#[derive(Resource, Default, Clone, Debug)]
+pub struct LevelStats {
+  /// tamed boids that hit the bounds
+  pub boids_lost: u32,
+  pub boost_time: Duration,
+}
+
+/// Score of the last completed level
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub struct LevelScore {
+  pub rescued: u32,
+  pub rescue_points: u32,
+  pub time_bonus: u32,
+  pub lost_penalty: u32,
+  pub boost_penalty: u32,
+  pub total: u32,
+  pub stars: u8,
+}
+
+impl LevelScore {
+  pub fn calculate(lvl: &LevelInfo, stats: &LevelStats, rescued: u32, elapsed: Duration) -> Self {
+    let rescue_points = rescued * POINTS_PER_RESCUE;
+    let time_bonus = lvl.time_goal.map_or(0, |goal| {
+      goal.saturating_sub(elapsed).as_secs() as u32 * POINTS_PER_SECOND_LEFT
+    });
+    let lost_penalty = stats.boids_lost * PENALTY_PER_LOST_BOID;
+    let boost_penalty = stats.boost_time.as_secs() as u32 * PENALTY_PER_BOOST_SECOND;
+    let total = (rescue_points + time_bonus).saturating_sub(lost_penalty + boost_penalty);
+    let thresholds = lvl
+      .star_thresholds
+      .unwrap_or_else(|| StarThresholds::for_level(lvl));
+
+    Self {
+      rescued,
+      rescue_points,
+      time_bonus,
+      lost_penalty,
+      boost_penalty,
+      total,
+      stars: thresholds.stars(total),
+    }
+  }
+}
+
+pub fn reset_level_stats(mut stats: ResMut<LevelStats>) {
+  *stats = default();
+}
+
+pub fn track_boost_usage(
+  player: Res<PlayerInfo>,
+  mut stats: ResMut<LevelStats>,
+  time: Res<Time>,
+) {
+  if player.in_boost_mode {
+    stats.boost_time += time.delta();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::tests::corridor_level;
+
+  const THRESHOLDS: StarThresholds = StarThresholds {
+    one: 100,
+    two: 200,
+    three: 300,
+  };
+
+  #[test]
+  fn stars_start_at_each_threshold() {
+    let stars = [0, 99, 100, 199, 200, 299, 300, 10_000].map(|score| THRESHOLDS.stars(score));
+    assert_eq!(stars, [0, 0, 1, 1, 2, 2, 3, 3]);
+  }
+
+  #[test]
+  fn thresholds_follow_the_rescue_goal() {
+    // 2 spawn points with 10 boids each
+    let level = LevelInfo {
+      spawn_points: vec![Vec2::ZERO, Vec2::ONE],
+      rescue_goal: Some(5),
+      ..corridor_level()
+    };
+    assert_eq!(
+      StarThresholds::for_level(&level),
+      StarThresholds {
+        one: 500,
+        two: 1150,
+        three: 1800,
+      }
+    );
+  }
+
+  #[test]
+  fn thresholds_without_a_goal_start_at_half_the_boids() {
+    let level = LevelInfo {
+      spawn_points: vec![Vec2::ZERO, Vec2::ONE],
+      ..corridor_level()
+    };
+    assert_eq!(
+      StarThresholds::for_level(&level),
+      StarThresholds {
+        one: 1000,
+        two: 1400,
+        three: 1800,
+      }
+    );
+  }
+
+  #[test]
+  fn score_adds_bonuses_and_subtracts_penalties() {
+    let level = LevelInfo {
+      time_goal: Some(Duration::from_secs(60)),
+      star_thresholds: Some(StarThresholds {
+        one: 500,
+        two: 1000,
+        three: 1500,
+      }),
+      ..corridor_level()
+    };
+    let stats = LevelStats {
+      boids_lost: 2,
+      boost_time: Duration::from_secs_f32(3.9),
+    };
+    assert_eq!(
+      LevelScore::calculate(&level, &stats, 10, Duration::from_secs_f32(30.5)),
+      LevelScore {
+        rescued: 10,
+        rescue_points: 1000,
+        // only whole seconds count
+        time_bonus: 290,
+        lost_penalty: 100,
+        boost_penalty: 15,
+        total: 1175,
+        stars: 2,
+      }
+    );
+  }
+
+  #[test]
+  fn score_never_goes_below_zero() {
+    let stats = LevelStats {
+      boids_lost: 10,
+      boost_time: Duration::ZERO,
+    };
+    let score = LevelScore::calculate(&corridor_level(), &stats, 1, Duration::from_secs(10));
+    assert_eq!(score.time_bonus, 0);
+    assert_eq!(score.total, 0);
+    assert_eq!(score.stars, 0);
+  }
+}
@@ -0,0 +1,322 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, render::render_resource::Shader};
+use sdfu::SDF;
+use serde::{Deserialize, Serialize};
+
+/// Serializable SDF tree used to describe level geometry
+/// the same tree is evaluated on the CPU for collision and compiled to WGSL for `bevy_smud`
+/// so both always agree. boxes are defined by their half extents, same as `smud::sd_box`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Shape {
+  Box(Vec2),
+  Circle(f32),
+  Triangle(Vec2, Vec2, Vec2),
+  Translate(Vec2, Box<Shape>),
+  Union(Vec<Shape>),
+  /// first shape with the second shape cut out of it
+  Subtract(Box<Shape>, Box<Shape>),
+  /// point reflection through the origin
+  Flip(Box<Shape>),
+}
+
+impl Shape {
+  pub fn rect(center: Vec2, half_size: Vec2) -> Self {
+    Shape::Box(half_size).translate(center)
+  }
+
+  pub fn translate(self, offset: Vec2) -> Self {
+    Shape::Translate(offset, Box::new(self))
+  }
+
+  pub fn union(self, other: Shape) -> Self {
+    match self {
+      Shape::Union(mut shapes) => {
+        shapes.push(other);
+        Shape::Union(shapes)
+      }
+      _ => Shape::Union(vec![self, other]),
+    }
+  }
+
+  pub fn subtract(self, other: Shape) -> Self {
+    Shape::Subtract(Box::new(self), Box::new(other))
+  }
+
+  pub fn flip(self) -> Self {
+    Shape::Flip(Box::new(self))
+  }
+
+  pub fn distance(&self, p: Vec2) -> f32 {
+    match self {
+      Shape::Box(half_size) => sd_box(p, *half_size),
+      Shape::Circle(radius) => p.length() - radius,
+      Shape::Triangle(p0, p1, p2) => sd_triangle(p, *p0, *p1, *p2),
+      Shape::Translate(offset, shape) => shape.distance(p - *offset),
+      Shape::Union(shapes) => shapes
+        .iter()
+        .map(|s| s.distance(p))
+        .fold(f32::INFINITY, f32::min),
+      Shape::Subtract(a, b) => a.distance(p).max(-b.distance(p)),
+      Shape::Flip(shape) => shape.distance(-p),
+    }
+  }
+
+  /// Radius of a circle around the origin that contains the shape
+  pub fn extent(&self) -> f32 {
+    match self {
+      Shape::Box(half_size) => half_size.length(),
+      Shape::Circle(radius) => *radius,
+      Shape::Triangle(p0, p1, p2) => p0.length().max(p1.length()).max(p2.length()),
+      Shape::Translate(offset, shape) => offset.length() + shape.extent(),
+      Shape::Union(shapes) => shapes.iter().map(Shape::extent).fold(0.0, f32::max),
+      Shape::Subtract(a, _) => a.extent(),
+      Shape::Flip(shape) => shape.extent(),
+    }
+  }
+
+  /// Generates a shader with an `sdf()` function usable as a `SmudShape::sdf`
+  /// `import_path` should be unique for every generated shader
+  pub fn to_wgsl(&self, import_path: &str) -> String {
+    let mut body = String::new();
+    let mut count = 0;
+    let result = self.write_wgsl(&mut body, &mut count, "p_in");
+    format!(
+      "#define_import_path {import_path}\n\n#import smud\n\nfn sdf(p_in: vec2<f32>) -> f32 {{\n{body}  return {result};\n}}\n"
+    )
+  }
+
+  pub fn shader(&self, import_path: &str) -> Shader {
+    Shader::from_wgsl(self.to_wgsl(import_path), format!("{import_path}.wgsl"))
+  }
+
+  /// writes a `let` binding for this node and returns its name
+  fn write_wgsl(&self, out: &mut String, count: &mut usize, p: &str) -> String {
+    let expr = match self {
+      Shape::Box(half_size) => format!("smud::sd_box({p}, {})", wgsl_vec2(*half_size)),
+      Shape::Circle(radius) => format!("smud::sd_circle({p}, {})", wgsl_f32(*radius)),
+      Shape::Triangle(p0, p1, p2) => format!(
+        "smud::sd_triangle({p}, {}, {}, {})",
+        wgsl_vec2(*p0),
+        wgsl_vec2(*p1),
+        wgsl_vec2(*p2)
+      ),
+      Shape::Translate(offset, shape) => {
+        return shape.write_wgsl(out, count, &format!("({p} - {})", wgsl_vec2(*offset)));
+      }
+      Shape::Flip(shape) => {
+        return shape.write_wgsl(out, count, &format!("(-{p})"));
+      }
+      Shape::Union(shapes) => {
+        let names: Vec<_> = shapes
+          .iter()
+          .map(|s| s.write_wgsl(out, count, p))
+          .collect();
+        let Some((first, rest)) = names.split_first() else {
+          // empty union is empty space
+          return "1e20".to_owned();
+        };
+        rest.iter().fold(first.clone(), |acc, n| {
+          format!("smud::op_union({acc}, {n})")
+        })
+      }
+      Shape::Subtract(a, b) => {
+        let a = a.write_wgsl(out, count, p);
+        let b = b.write_wgsl(out, count, p);
+        format!("smud::op_subtract({b}, {a})")
+      }
+    };
+    let name = format!("d{count}");
+    *count += 1;
+    writeln!(out, "  let {name} = {expr};").unwrap();
+    name
+  }
+}
+
+/// Debug formatting keeps every digit of the f32, WGSL also needs a '.' in float literals
+fn wgsl_f32(v: f32) -> String {
+  let s = format!("{v:?}");
+  if s.contains('.') {
+    s
+  } else if let Some((mantissa, exponent)) = s.split_once('e') {
+    format!("{mantissa}.0e{exponent}")
+  } else {
+    format!("{s}.0")
+  }
+}
+
+fn wgsl_vec2(v: Vec2) -> String {
+  format!("vec2<f32>({}, {})", wgsl_f32(v.x), wgsl_f32(v.y))
+}
+
+impl SDF<f32, Vec2> for Shape {
+  #[inline]
+  fn dist(&self, p: Vec2) -> f32 {
+    self.distance(p)
+  }
+}
+
+fn sd_box(p: Vec2, half_size: Vec2) -> f32 {
+  let d = p.abs() - half_size;
+  d.max(Vec2::ZERO).length() + d.x.max(d.y).min(0.0)
+}
+
+fn sd_triangle(p: Vec2, p0: Vec2, p1: Vec2, p2: Vec2) -> f32 {
+  let e0 = p1 - p0;
+  let e1 = p2 - p1;
+  let e2 = p0 - p2;
+  let v0 = p - p0;
+  let v1 = p - p1;
+  let v2 = p - p2;
+  let pq0 = v0 - e0 * (v0.dot(e0) / e0.dot(e0)).clamp(0.0, 1.0);
+  let pq1 = v1 - e1 * (v1.dot(e1) / e1.dot(e1)).clamp(0.0, 1.0);
+  let pq2 = v2 - e2 * (v2.dot(e2) / e2.dot(e2)).clamp(0.0, 1.0);
+  let s = (e0.x * e2.y - e0.y * e2.x).signum();
+  let d = Vec2::new(pq0.dot(pq0), s * (v0.x * e0.y - v0.y * e0.x))
+    .min(Vec2::new(pq1.dot(pq1), s * (v1.x * e1.y - v1.y * e1.x)))
+    .min(Vec2::new(pq2.dot(pq2), s * (v2.x * e2.y - v2.y * e2.x)));
+  -d.x.sqrt() * d.y.signum()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// expected shader for the `let` bindings in `body`
+  fn shader(body: &[&str], result: &str) -> String {
+    let body: String = body.iter().map(|line| format!("  {line}\n")).collect();
+    format!(
+      "#define_import_path test::shape\n\n#import smud\n\nfn sdf(p_in: vec2<f32>) -> f32 {{\n{body}  return {result};\n}}\n"
+    )
+  }
+
+  #[test]
+  fn box_wgsl() {
+    assert_eq!(
+      Shape::Box(Vec2::new(1., 2.5)).to_wgsl("test::shape"),
+      "#define_import_path test::shape
+
+#import smud
+
+fn sdf(p_in: vec2<f32>) -> f32 {
+  let d0 = smud::sd_box(p_in, vec2<f32>(1.0, 2.5));
+  return d0;
+}
+"
+    );
+  }
+
+  #[test]
+  fn circle_wgsl() {
+    assert_eq!(
+      Shape::Circle(3.).to_wgsl("test::shape"),
+      shader(&["let d0 = smud::sd_circle(p_in, 3.0);"], "d0")
+    );
+  }
+
+  #[test]
+  fn triangle_wgsl() {
+    let triangle = Shape::Triangle(Vec2::ZERO, Vec2::X, Vec2::new(0., -1.));
+    assert_eq!(
+      triangle.to_wgsl("test::shape"),
+      shader(
+        &["let d0 = smud::sd_triangle(p_in, vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(0.0, -1.0));"],
+        "d0"
+      )
+    );
+  }
+
+  #[test]
+  fn translate_wgsl() {
+    assert_eq!(
+      Shape::Circle(3.).translate(Vec2::new(1., -2.)).to_wgsl("test::shape"),
+      shader(
+        &["let d0 = smud::sd_circle((p_in - vec2<f32>(1.0, -2.0)), 3.0);"],
+        "d0"
+      )
+    );
+  }
+
+  #[test]
+  fn union_wgsl() {
+    let union = Shape::Circle(1.)
+      .union(Shape::Circle(2.))
+      .union(Shape::Circle(3.));
+    assert_eq!(
+      union.to_wgsl("test::shape"),
+      shader(
+        &[
+          "let d0 = smud::sd_circle(p_in, 1.0);",
+          "let d1 = smud::sd_circle(p_in, 2.0);",
+          "let d2 = smud::sd_circle(p_in, 3.0);",
+          "let d3 = smud::op_union(smud::op_union(d0, d1), d2);",
+        ],
+        "d3"
+      )
+    );
+    assert_eq!(
+      Shape::Union(Vec::new()).to_wgsl("test::shape"),
+      shader(&[], "1e20")
+    );
+  }
+
+  #[test]
+  fn subtract_wgsl() {
+    // smud::op_subtract(a, b) cuts a out of b
+    let subtract = Shape::Box(Vec2::splat(2.)).subtract(Shape::Circle(1.));
+    assert_eq!(
+      subtract.to_wgsl("test::shape"),
+      shader(
+        &[
+          "let d0 = smud::sd_box(p_in, vec2<f32>(2.0, 2.0));",
+          "let d1 = smud::sd_circle(p_in, 1.0);",
+          "let d2 = smud::op_subtract(d1, d0);",
+        ],
+        "d2"
+      )
+    );
+  }
+
+  #[test]
+  fn flip_wgsl() {
+    let flip = Shape::Circle(3.).translate(Vec2::new(1., 2.)).flip();
+    assert_eq!(
+      flip.to_wgsl("test::shape"),
+      shader(
+        &["let d0 = smud::sd_circle(((-p_in) - vec2<f32>(1.0, 2.0)), 3.0);"],
+        "d0"
+      )
+    );
+  }
+
+  #[test]
+  fn wgsl_floats_keep_full_precision() {
+    assert_eq!(wgsl_f32(2.), "2.0");
+    assert_eq!(wgsl_f32(-0.5), "-0.5");
+    assert_eq!(wgsl_f32(3333.3333), "3333.3333");
+    assert_eq!(wgsl_f32(0.0001), "0.0001");
+    assert_eq!(wgsl_f32(1e20), "1.0e20");
+    assert_eq!(wgsl_f32(1.5e-20), "1.5e-20");
+  }
+
+  #[test]
+  fn distances_match_hand_computed_values() {
+    let p = Vec2::new(3., 4.);
+    assert_eq!(Shape::Circle(1.).distance(p), 4.);
+    assert_eq!(Shape::Box(Vec2::new(1., 2.)).distance(p), 8f32.sqrt());
+    assert_eq!(Shape::Box(Vec2::new(4., 5.)).distance(p), -1.);
+    // right triangle with its legs on the axes, closest to the hypotenuse
+    let triangle = Shape::Triangle(Vec2::ZERO, Vec2::new(4., 0.), Vec2::new(0., 4.));
+    assert!((triangle.distance(Vec2::new(3., 3.)) - 2f32.sqrt()).abs() < 1e-5);
+    assert!((triangle.distance(Vec2::new(1., 1.)) + 1.).abs() < 1e-5);
+    let moved = Shape::Circle(1.).translate(Vec2::new(3., 0.));
+    assert_eq!(moved.distance(p), 3.);
+    assert_eq!(moved.clone().flip().distance(-p), 3.);
+    assert_eq!(Shape::Circle(1.).union(moved).distance(p), 3.);
+    // solid ring between radius 4 and 6, `p` is at radius 5
+    let ring = Shape::Circle(6.).subtract(Shape::Circle(4.));
+    assert_eq!(ring.distance(p), -1.);
+    assert_eq!(ring.distance(Vec2::ZERO), 4.);
+    assert_eq!(ring.distance(Vec2::new(0., 8.)), 2.);
+  }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::Initializing;
+use crate::{
+  level::{LevelId, LevelManager, LevelRegistry, LevelRegistryError},
+  Initializing,
+};
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 pub enum SimulationState {
@@ -14,8 +17,11 @@ pub enum SimulationState {
   /// Phase to determine which level to start
   ChoosingLevel,
   /// Simulation is running
-  /// this can only transition to LevelComplete or GameOver
+  /// this can only transition to LevelComplete, GameOver or Paused
   Simulating, // main phase, create UI here
+  /// Simulation is frozen mid level
+  /// can go back to Simulating, restart the level (ChoosingLevel) or quit (Ready)
+  Paused,
   /// level has been completed
   /// chance to show summary before next level is loaded
   /// can transition to either Complete or Loading
@@ -25,6 +31,9 @@ pub enum SimulationState {
   /// Player failed objectives
   /// can only transition to Loading (to reload the level)
   GameOver(GameOverReason),
+  /// Something went wrong that the simulation can not recover from, see `SimulationErrors`
+  /// can only go back to Ready (Quit)
+  Error,
 }
 
 #[derive(Copy, Debug, Clone, Eq,PartialEq, Hash)]
@@ -34,49 +43,379 @@ pub enum GameOverReason {
   OutOfBoids
 }
 
-#[derive(Event, Debug)]
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
 pub enum GameControlCommand {
   Initialize,
   StartGame,
+  /// like `StartGame` but starting from any unlocked level
+  StartLevel(LevelId),
   NextLevel,
   Retry,
+  Pause,
+  Resume,
+  /// abandon the current game, the simulation goes back to Ready
+  Quit,
+}
+
+/// Sent when a command is not valid in the current state
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct CommandRejected {
+  pub state: SimulationState,
+  pub command: GameControlCommand,
+}
+
+/// Which states a transition applies to
+#[derive(Debug, Clone, Copy)]
+enum FromState {
+  Is(SimulationState),
+  AnyGameOver,
+}
+
+impl FromState {
+  fn matches(&self, state: &SimulationState) -> bool {
+    match self {
+      FromState::Is(s) => s == state,
+      FromState::AnyGameOver => matches!(state, SimulationState::GameOver(_)),
+    }
+  }
+}
+
+/// Every transition that can be triggered by a `GameControlCommand`
+const TRANSITIONS: &[(FromState, fn(&GameControlCommand) -> bool, SimulationState)] = &[
+  // set state so exclusive system to register module runs
+  (
+    FromState::Is(SimulationState::Disabled),
+    |c| matches!(c, GameControlCommand::Initialize),
+    SimulationState::Initializing,
+  ),
+  // signal that we want to load a level and play
+  (
+    FromState::Is(SimulationState::Ready),
+    |c| matches!(c, GameControlCommand::StartGame),
+    SimulationState::ChoosingLevel,
+  ),
+  // jump straight to a level, only unlocked levels are accepted
+  (
+    FromState::Is(SimulationState::Ready),
+    |c| matches!(c, GameControlCommand::StartLevel(_)),
+    SimulationState::ChoosingLevel,
+  ),
+  // level has been completed, signal that we want to unload current level and load next level
+  (
+    FromState::Is(SimulationState::LevelComplete),
+    |c| matches!(c, GameControlCommand::NextLevel),
+    SimulationState::ChoosingLevel,
+  ),
+  (
+    FromState::AnyGameOver,
+    |c| matches!(c, GameControlCommand::Retry),
+    SimulationState::ChoosingLevel,
+  ),
+  (
+    FromState::Is(SimulationState::Simulating),
+    |c| matches!(c, GameControlCommand::Pause),
+    SimulationState::Paused,
+  ),
+  (
+    FromState::Is(SimulationState::Paused),
+    |c| matches!(c, GameControlCommand::Resume),
+    SimulationState::Simulating,
+  ),
+  // current level is not complete so it gets reloaded
+  (
+    FromState::Is(SimulationState::Paused),
+    |c| matches!(c, GameControlCommand::Retry),
+    SimulationState::ChoosingLevel,
+  ),
+  (
+    FromState::Is(SimulationState::Paused),
+    |c| matches!(c, GameControlCommand::Quit),
+    SimulationState::Ready,
+  ),
+  (
+    FromState::Is(SimulationState::Error),
+    |c| matches!(c, GameControlCommand::Quit),
+    SimulationState::Ready,
+  ),
+];
+
+impl SimulationState {
+  /// State `cmd` moves the simulation to, `None` if it is not allowed from this state
+  pub fn transition(&self, cmd: &GameControlCommand) -> Option<SimulationState> {
+    TRANSITIONS
+      .iter()
+      .find(|(from, accepts, _)| from.matches(self) && accepts(cmd))
+      .map(|(_, _, to)| *to)
+  }
 }
 
 pub fn process_game_control_commands(
   mut cmds: EventReader<GameControlCommand>,
+  mut rejected: EventWriter<CommandRejected>,
   mut next_sim_state: ResMut<NextState<SimulationState>>,
   current_state: Res<State<SimulationState>>,
+  mut lvl_mgr: ResMut<LevelManager>,
+  lvl_reg: Res<LevelRegistry>,
 ) {
+  let state = *current_state.get();
   for cmd in cmds.read() {
-    info!("{current_state:?} {cmd:?}");
-    match ((*current_state).get(), cmd) {
-      (SimulationState::Disabled, GameControlCommand::Initialize) => {
-        // set state so exclusive system to register module runs
-        next_sim_state.set(SimulationState::Initializing);
-      }
-      (SimulationState::Ready, GameControlCommand::StartGame) => {
-        // signal that we want to load a level and play
-        next_sim_state.set(SimulationState::ChoosingLevel)
-      }
-      (SimulationState::LevelComplete, GameControlCommand::NextLevel) => {
-        // level has been completed, signal that we want to unload current level and load next level
-        next_sim_state.set(SimulationState::ChoosingLevel)
-      }
-      (SimulationState::GameOver(_), GameControlCommand::Retry) => {
-        next_sim_state.set(SimulationState::ChoosingLevel)
-      }
-      _ => {
-        unimplemented!()
-      }
+    info!("{state:?} {cmd:?}");
+    let next = state.transition(cmd).filter(|_| match cmd {
+      GameControlCommand::StartLevel(id) => lvl_mgr.is_unlocked(id, &lvl_reg),
+      _ => true,
+    });
+    let Some(next) = next else {
+      warn!("rejected {cmd:?} in {state:?}");
+      rejected.send(CommandRejected {
+        state,
+        command: cmd.clone(),
+      });
+      continue;
+    };
+    if let GameControlCommand::StartLevel(id) = cmd {
+      lvl_mgr.load_level(id);
+    }
+    next_sim_state.set(next);
+  }
+}
+
+/// Sent when the simulation can not continue, moves it to `SimulationState::Error`
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SimulationFailed(pub String);
+
+impl From<LevelRegistryError> for SimulationFailed {
+  fn from(value: LevelRegistryError) -> Self {
+    Self(value.to_string())
+  }
+}
+
+/// Why the simulation is in `SimulationState::Error`, cleared when leaving it
+#[derive(Resource, Default, Debug)]
+pub struct SimulationErrors(pub Vec<String>);
+
+pub fn process_simulation_failures(
+  mut failures: EventReader<SimulationFailed>,
+  mut errors: ResMut<SimulationErrors>,
+  mut next_sim_state: ResMut<NextState<SimulationState>>,
+) {
+  for failure in failures.read() {
+    error!("simulation failed: {}", failure.0);
+    if !errors.0.contains(&failure.0) {
+      errors.0.push(failure.0.clone());
     }
+    next_sim_state.set(SimulationState::Error);
   }
 }
 
+pub fn clear_simulation_errors(mut errors: ResMut<SimulationErrors>) {
+  errors.0.clear();
+}
+
+/// Levels are checked once all modules are done, broken links fail the simulation
 pub fn wait_until_initialization_complete(
   qry: Query<Entity, With<Initializing>>,
   mut next_state: ResMut<NextState<SimulationState>>,
+  mut failed: EventWriter<SimulationFailed>,
+  lvl_reg: Res<LevelRegistry>,
 ) {
-  if qry.is_empty() {
-    next_state.set(SimulationState::Ready);
+  if !qry.is_empty() {
+    return;
+  }
+  match lvl_reg.validate() {
+    Ok(()) => next_state.set(SimulationState::Ready),
+    Err(errors) => failed.send_batch(errors.into_iter().map(SimulationFailed::from)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{boid::WallAvoidance, level::LevelInfo, moveable::CollisionResponse};
+
+  const STATES: [SimulationState; 12] = [
+    SimulationState::Disabled,
+    SimulationState::Initializing,
+    SimulationState::Ready,
+    SimulationState::ChoosingLevel,
+    SimulationState::Simulating,
+    SimulationState::Paused,
+    SimulationState::LevelComplete,
+    SimulationState::GameComplete,
+    SimulationState::GameOver(GameOverReason::OutOfBounds),
+    SimulationState::GameOver(GameOverReason::OutOfTime),
+    SimulationState::GameOver(GameOverReason::OutOfBoids),
+    SimulationState::Error,
+  ];
+
+  fn commands() -> [GameControlCommand; 8] {
+    [
+      GameControlCommand::Initialize,
+      GameControlCommand::StartGame,
+      GameControlCommand::StartLevel(LevelId::from(1)),
+      GameControlCommand::NextLevel,
+      GameControlCommand::Retry,
+      GameControlCommand::Pause,
+      GameControlCommand::Resume,
+      GameControlCommand::Quit,
+    ]
+  }
+
+  fn expected(state: SimulationState, cmd: GameControlCommand) -> Option<SimulationState> {
+    use GameControlCommand as C;
+    use SimulationState as S;
+    match (state, cmd) {
+      (S::Disabled, C::Initialize) => Some(S::Initializing),
+      (S::Ready, C::StartGame) => Some(S::ChoosingLevel),
+      (S::Ready, C::StartLevel(_)) => Some(S::ChoosingLevel),
+      (S::LevelComplete, C::NextLevel) => Some(S::ChoosingLevel),
+      (S::GameOver(_), C::Retry) => Some(S::ChoosingLevel),
+      (S::Simulating, C::Pause) => Some(S::Paused),
+      (S::Paused, C::Resume) => Some(S::Simulating),
+      (S::Paused, C::Retry) => Some(S::ChoosingLevel),
+      (S::Paused, C::Quit) => Some(S::Ready),
+      (S::Error, C::Quit) => Some(S::Ready),
+      _ => None,
+    }
+  }
+
+  #[test]
+  fn every_transition() {
+    for state in STATES {
+      for cmd in commands() {
+        assert_eq!(
+          state.transition(&cmd),
+          expected(state, cmd.clone()),
+          "{cmd:?} in {state:?}"
+        );
+      }
+    }
+  }
+
+  fn empty_level() -> LevelInfo {
+    LevelInfo {
+      name: String::new(),
+      bounds: default(),
+      bake_bounds: None,
+      finish_bounds_box: default(),
+      finish_bounds: Vec4::ZERO,
+      bounds_sdf: None,
+      obstacle_sdfs: Vec::new(),
+      starting_point: Vec2::ZERO,
+      next_level: None,
+      spawn_points: Vec::new(),
+      boids_per_spawn_point: 0,
+      rescue_goal: None,
+      time_goal: None,
+      wander: false,
+      wall_avoidance: WallAvoidance::Probes,
+      collision: CollisionResponse::Stop,
+      star_thresholds: None,
+    }
+  }
+
+  fn app_in(state: SimulationState) -> App {
+    let mut app = App::new();
+    app
+      .add_state::<SimulationState>()
+      .add_event::<GameControlCommand>()
+      .add_event::<CommandRejected>()
+      .init_resource::<LevelManager>()
+      .insert_resource(LevelRegistry {
+        levels: [1, 2].map(|id| (LevelId::from(id), empty_level())).into(),
+        start_level: Some(LevelId::from(1)),
+      })
+      .add_systems(Update, process_game_control_commands);
+    app.world.insert_resource(State::new(state));
+    app
+  }
+
+  fn rejected(app: &App) -> Vec<CommandRejected> {
+    let events = app.world.resource::<Events<CommandRejected>>();
+    events.get_reader().read(events).cloned().collect()
+  }
+
+  #[test]
+  fn invalid_command_is_rejected() {
+    let mut app = app_in(SimulationState::Simulating);
+    app.world.send_event(GameControlCommand::NextLevel);
+    app.update();
+
+    assert_eq!(
+      app.world.resource::<State<SimulationState>>().get(),
+      &SimulationState::Simulating
+    );
+    assert_eq!(
+      rejected(&app),
+      vec![CommandRejected {
+        state: SimulationState::Simulating,
+        command: GameControlCommand::NextLevel,
+      }]
+    );
+  }
+
+  #[test]
+  fn valid_command_changes_state() {
+    let mut app = app_in(SimulationState::GameOver(GameOverReason::OutOfTime));
+    app.world.send_event(GameControlCommand::Retry);
+    app.update();
+    app.update();
+
+    assert_eq!(
+      app.world.resource::<State<SimulationState>>().get(),
+      &SimulationState::ChoosingLevel
+    );
+    assert!(rejected(&app).is_empty());
+  }
+
+  #[test]
+  fn rejected_command_does_not_override_valid_one() {
+    let mut app = app_in(SimulationState::Simulating);
+    app.world.send_event(GameControlCommand::Pause);
+    app.world.send_event(GameControlCommand::Quit);
+    app.update();
+    app.update();
+
+    assert_eq!(
+      app.world.resource::<State<SimulationState>>().get(),
+      &SimulationState::Paused
+    );
+    assert_eq!(rejected(&app).len(), 1);
+  }
+
+  #[test]
+  fn locked_level_is_rejected() {
+    let mut app = app_in(SimulationState::Ready);
+    app.world.send_event(GameControlCommand::StartLevel(LevelId::from(2)));
+    app.update();
+    app.update();
+
+    assert_eq!(
+      app.world.resource::<State<SimulationState>>().get(),
+      &SimulationState::Ready
+    );
+    assert_eq!(rejected(&app).len(), 1);
+    assert_eq!(app.world.resource::<LevelManager>().load_next, None);
+  }
+
+  #[test]
+  fn start_level_loads_that_level() {
+    let mut app = app_in(SimulationState::Ready);
+    app
+      .world
+      .resource_mut::<LevelManager>()
+      .unlocked
+      .insert(LevelId::from(2));
+    app.world.send_event(GameControlCommand::StartLevel(LevelId::from(2)));
+    app.update();
+    app.update();
+
+    assert_eq!(
+      app.world.resource::<State<SimulationState>>().get(),
+      &SimulationState::ChoosingLevel
+    );
+    assert_eq!(
+      app.world.resource::<LevelManager>().load_next,
+      Some(LevelId::from(2))
+    );
   }
 }
@@ -0,0 +1,855 @@
+use bevy::{
+  input::mouse::{MouseMotion, MouseWheel},
+  prelude::*,
+  window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContexts};
+use jam4::{
+  boid::WallAvoidance,
+  level::{LevelFile, LevelId, LevelManager, LevelRegistry, TerrainFile},
+  moveable::CollisionResponse,
+  shape::Shape,
+  GameControlCommand, SimulationState,
+};
+use utils::despawn_screen;
+
+use crate::game::GameNextState;
+
+/// Space around the pieces that is filled with terrain
+const FRAME_MARGIN: f32 = 3000.0;
+/// Distance in pixels to grab a handle
+const HANDLE_RADIUS: f32 = 12.0;
+
+pub trait EditorExtensions {
+  /// F2 on the title screen opens the editor, play-testing goes through `game_state`
+  fn add_level_editor<T: States + Copy>(
+    &mut self,
+    editor_state: T,
+    title_state: T,
+    game_state: T,
+  ) -> &mut Self;
+}
+
+impl EditorExtensions for App {
+  fn add_level_editor<T: States + Copy>(
+    &mut self,
+    editor_state: T,
+    title_state: T,
+    game_state: T,
+  ) -> &mut Self {
+    self
+      .init_resource::<EditorState>()
+      .insert_resource(EditorStates {
+        editor: editor_state,
+        title: title_state,
+        game: game_state,
+      })
+      .add_systems(OnEnter(editor_state), setup_editor::<T>)
+      .add_systems(OnExit(editor_state), despawn_screen::<OnEditorScreen>)
+      .add_systems(
+        Update,
+        open_editor::<T>
+          .run_if(in_state(title_state))
+          .run_if(in_state(SimulationState::Ready)),
+      )
+      .add_systems(
+        Update,
+        (
+          move_camera,
+          editor_panel::<T>,
+          edit_level,
+          draw_level,
+          close_editor::<T>,
+        )
+          .chain()
+          .run_if(in_state(editor_state)),
+      )
+  }
+}
+
+#[derive(Resource)]
+struct EditorStates<T> {
+  editor: T,
+  title: T,
+  game: T,
+}
+
+#[derive(Component)]
+struct OnEditorScreen;
+
+#[derive(Component)]
+struct EditorCamera;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PieceShape {
+  Box { half_size: Vec2 },
+  /// vertices relative to the piece center
+  Triangle([Vec2; 3]),
+}
+
+/// Open pieces are combined into the playable area, walls are cut out of it
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Piece {
+  center: Vec2,
+  shape: PieceShape,
+  wall: bool,
+}
+
+impl Piece {
+  fn to_shape(self) -> Shape {
+    match self.shape {
+      PieceShape::Box { half_size } => Shape::rect(self.center, half_size),
+      PieceShape::Triangle([a, b, c]) => {
+        Shape::Triangle(self.center + a, self.center + b, self.center + c)
+      }
+    }
+  }
+
+  fn points(&self) -> Vec<Vec2> {
+    match self.shape {
+      PieceShape::Box { half_size } => vec![self.center - half_size, self.center + half_size],
+      PieceShape::Triangle(vertices) => vertices.iter().map(|v| self.center + *v).collect(),
+    }
+  }
+}
+
+/// Something that can be dragged with the mouse
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Grab {
+  Piece(usize),
+  BoxCorner(usize),
+  Vertex(usize, usize),
+  Spawn(usize),
+  Start,
+  Finish,
+  FinishCorner,
+}
+
+#[derive(Resource)]
+struct EditorState {
+  pieces: Vec<Piece>,
+  /// everything except `id` and `bounds`, which come from `id` and `pieces`
+  level: LevelFile,
+  id: String,
+  export_path: String,
+  selected: Option<Grab>,
+  dragging: bool,
+  status: String,
+  /// play-test shaders need unique import paths
+  playtests: u32,
+  /// terrain of the current play-test, removed when the next one starts
+  playtest_sdf: Option<Handle<Shader>>,
+}
+
+impl Default for EditorState {
+  fn default() -> Self {
+    Self {
+      pieces: vec![Piece {
+        center: Vec2::ZERO,
+        shape: PieceShape::Box {
+          half_size: Vec2::new(2000.0, 6000.0),
+        },
+        wall: false,
+      }],
+      level: LevelFile {
+        id: LevelId::new("custom", "new_level").expect("default id is valid"),
+        name: "New level".to_owned(),
+        bounds: Shape::Box(Vec2::ZERO),
+        finish_bounds: Vec4::new(0.0, 5500.0, 500.0, 300.0),
+        starting_point: Vec2::new(0.0, -5000.0),
+        spawn_points: vec![Vec2::ZERO],
+        boids_per_spawn_point: 20,
+        rescue_goal: None,
+        time_goal: None,
+        wander: false,
+        wall_avoidance: WallAvoidance::Probes,
+        collision: CollisionResponse::Stop,
+        next_level: None,
+        star_thresholds: None,
+        obstacles: Vec::new(),
+        bake_bounds: None,
+        terrain: TerrainFile::default(),
+      },
+      id: "custom:new_level".to_owned(),
+      export_path: "assets/levels/new_level.level.ron".to_owned(),
+      selected: None,
+      dragging: false,
+      status: String::new(),
+      playtests: 0,
+      playtest_sdf: None,
+    }
+  }
+}
+
+impl EditorState {
+  /// Playable area is the union of open pieces minus the walls, everything around it is terrain
+  fn bounds(&self) -> Result<Shape, String> {
+    let (walls, open): (Vec<_>, Vec<_>) = self.pieces.iter().partition(|p| p.wall);
+    if open.is_empty() {
+      return Err("add at least one open piece".to_owned());
+    }
+    let points: Vec<Vec2> = self.pieces.iter().flat_map(Piece::points).collect();
+    let min = points.iter().copied().fold(Vec2::MAX, Vec2::min);
+    let max = points.iter().copied().fold(Vec2::MIN, Vec2::max);
+
+    let union =
+      |pieces: Vec<&Piece>| Shape::Union(pieces.into_iter().map(|p| p.to_shape()).collect());
+    let mut playable = union(open);
+    if !walls.is_empty() {
+      playable = playable.subtract(union(walls));
+    }
+    Ok(Shape::rect((min + max) / 2.0, (max - min) / 2.0 + FRAME_MARGIN).subtract(playable))
+  }
+
+  fn build(&self) -> Result<LevelFile, String> {
+    let mut file = self.level.clone();
+    file.id = self.id.parse::<LevelId>().map_err(|e| e.to_string())?;
+    file.bounds = self.bounds()?;
+    file.validate().map_err(|e| e.to_string())?;
+    Ok(file)
+  }
+
+  /// Continues editing `file`, its bounds have to be made of pieces like the ones `bounds` builds
+  fn open(&mut self, file: LevelFile) -> Result<(), String> {
+    let Shape::Subtract(_frame, playable) = &file.bounds else {
+      return Err(format!(
+        "the bounds of {} are not cut out of a frame",
+        file.id
+      ));
+    };
+    let mut pieces = Vec::new();
+    let parsed = match playable.as_ref() {
+      Shape::Subtract(open, walls) => {
+        parse_pieces(open, false, &mut pieces) && parse_pieces(walls, true, &mut pieces)
+      }
+      open => parse_pieces(open, false, &mut pieces),
+    };
+    if !parsed {
+      return Err(format!(
+        "the bounds of {} are not made of boxes and triangles",
+        file.id
+      ));
+    }
+    self.pieces = pieces;
+    self.id = file.id.to_string();
+    self.level = file;
+    self.selected = None;
+    self.dragging = false;
+    Ok(())
+  }
+
+  fn load(&mut self, path: &str) -> Result<(), String> {
+    let file = LevelFile::parse(&read_level(path)?).map_err(|e| e.to_string())?;
+    self.open(file)
+  }
+
+  fn export(&self) -> Result<(), String> {
+    let file = self.build()?;
+    let contents = ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())
+      .map_err(|e| e.to_string())?;
+    write_level(&self.export_path, &contents)
+  }
+
+  fn handles(&self) -> Vec<(Grab, Vec2)> {
+    let mut handles = Vec::new();
+    for (i, piece) in self.pieces.iter().enumerate() {
+      match piece.shape {
+        PieceShape::Box { half_size } => {
+          handles.push((Grab::BoxCorner(i), piece.center + half_size));
+        }
+        PieceShape::Triangle(vertices) => {
+          for (k, v) in vertices.iter().enumerate() {
+            handles.push((Grab::Vertex(i, k), piece.center + *v));
+          }
+        }
+      }
+      handles.push((Grab::Piece(i), piece.center));
+    }
+    for (i, p) in self.level.spawn_points.iter().enumerate() {
+      handles.push((Grab::Spawn(i), *p));
+    }
+    let finish = self.level.finish_bounds;
+    handles.push((Grab::FinishCorner, finish.xy() + finish.zw()));
+    handles.push((Grab::Finish, finish.xy()));
+    handles.push((Grab::Start, self.level.starting_point));
+    handles
+  }
+
+  fn move_grab(&mut self, grab: Grab, to: Vec2) {
+    match grab {
+      Grab::Piece(i) => self.pieces[i].center = to,
+      Grab::BoxCorner(i) => {
+        let center = self.pieces[i].center;
+        if let PieceShape::Box { half_size } = &mut self.pieces[i].shape {
+          *half_size = (to - center).abs().max(Vec2::splat(1.0));
+        }
+      }
+      Grab::Vertex(i, k) => {
+        let center = self.pieces[i].center;
+        if let PieceShape::Triangle(vertices) = &mut self.pieces[i].shape {
+          vertices[k] = to - center;
+        }
+      }
+      Grab::Spawn(i) => self.level.spawn_points[i] = to,
+      Grab::Start => self.level.starting_point = to,
+      Grab::Finish => {
+        self.level.finish_bounds.x = to.x;
+        self.level.finish_bounds.y = to.y;
+      }
+      Grab::FinishCorner => {
+        let half_size = (to - self.level.finish_bounds.xy()).abs().max(Vec2::splat(1.0));
+        self.level.finish_bounds.z = half_size.x;
+        self.level.finish_bounds.w = half_size.y;
+      }
+    }
+  }
+
+  fn delete_selected(&mut self) {
+    match self.selected.take() {
+      Some(Grab::Piece(i) | Grab::BoxCorner(i) | Grab::Vertex(i, _)) => {
+        self.pieces.remove(i);
+      }
+      Some(Grab::Spawn(i)) => {
+        self.level.spawn_points.remove(i);
+      }
+      _ => {}
+    }
+  }
+}
+
+/// Adds the pieces `shape` is a union of, false if it contains anything else
+fn parse_pieces(shape: &Shape, wall: bool, pieces: &mut Vec<Piece>) -> bool {
+  let piece = match shape {
+    Shape::Union(shapes) => return shapes.iter().all(|s| parse_pieces(s, wall, pieces)),
+    Shape::Box(half_size) => Piece {
+      center: Vec2::ZERO,
+      shape: PieceShape::Box {
+        half_size: *half_size,
+      },
+      wall,
+    },
+    Shape::Translate(center, shape) => match shape.as_ref() {
+      Shape::Box(half_size) => Piece {
+        center: *center,
+        shape: PieceShape::Box {
+          half_size: *half_size,
+        },
+        wall,
+      },
+      _ => return false,
+    },
+    Shape::Triangle(a, b, c) => {
+      let center = (*a + *b + *c) / 3.0;
+      Piece {
+        center,
+        shape: PieceShape::Triangle([*a - center, *b - center, *c - center]),
+        wall,
+      }
+    }
+    _ => return false,
+  };
+  pieces.push(piece);
+  true
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_level(path: &str) -> Result<Vec<u8>, String> {
+  std::fs::read(path).map_err(|e| format!("{path}: {e}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_level(_path: &str) -> Result<Vec<u8>, String> {
+  Err("loading files is not supported on the web".to_owned())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_level(path: &str, contents: &str) -> Result<(), String> {
+  std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_level(_path: &str, _contents: &str) -> Result<(), String> {
+  Err("exporting is not supported on the web".to_owned())
+}
+
+fn playtest_id() -> LevelId {
+  LevelId::new("editor", "playtest").expect("play-test id is valid")
+}
+
+fn open_editor<T: States + Copy>(
+  keyboard_input: Res<Input<KeyCode>>,
+  states: Res<EditorStates<T>>,
+  mut app_state: ResMut<NextState<T>>,
+) {
+  if keyboard_input.just_pressed(KeyCode::F2) {
+    app_state.set(states.editor);
+  }
+}
+
+/// The play-test level is dropped so it does not show up in the level select
+#[allow(clippy::too_many_arguments)]
+fn close_editor<T: States + Copy>(
+  keyboard_input: Res<Input<KeyCode>>,
+  states: Res<EditorStates<T>>,
+  mut app_state: ResMut<NextState<T>>,
+  mut game_next: ResMut<GameNextState<T>>,
+  mut state: ResMut<EditorState>,
+  mut shaders: ResMut<Assets<Shader>>,
+  mut lvl_reg: ResMut<LevelRegistry>,
+  mut lvl_mgr: ResMut<LevelManager>,
+  mut contexts: EguiContexts,
+) {
+  if keyboard_input.just_pressed(KeyCode::Escape) && !contexts.ctx_mut().wants_keyboard_input() {
+    let id = playtest_id();
+    lvl_reg.unregister(&id);
+    lvl_mgr.unlocked.remove(&id);
+    if let Some(sdf) = state.playtest_sdf.take() {
+      shaders.remove(&sdf);
+    }
+    game_next.0 = states.title;
+    app_state.set(states.title);
+  }
+}
+
+fn setup_editor<T: States + Copy>(
+  mut cmd: Commands,
+  mut state: ResMut<EditorState>,
+  states: Res<EditorStates<T>>,
+  mut game_next: ResMut<GameNextState<T>>,
+) {
+  // quitting a play-test comes back here
+  game_next.0 = states.editor;
+  state.selected = None;
+  state.dragging = false;
+
+  let mut cam = Camera2dBundle::default();
+  cam.projection.scale = 10.0;
+  cmd.spawn((cam, EditorCamera, OnEditorScreen));
+}
+
+/// Scroll to zoom, drag with the right mouse button to pan
+fn move_camera(
+  mut qry: Query<(&mut Transform, &mut OrthographicProjection), With<EditorCamera>>,
+  mut wheel: EventReader<MouseWheel>,
+  mut motion: EventReader<MouseMotion>,
+  buttons: Res<Input<MouseButton>>,
+  mut contexts: EguiContexts,
+) {
+  let over_ui = contexts.ctx_mut().wants_pointer_input();
+  let Ok((mut transform, mut projection)) = qry.get_single_mut() else {
+    return;
+  };
+  for event in wheel.read() {
+    if !over_ui {
+      projection.scale = (projection.scale * 1.1f32.powf(-event.y)).clamp(0.5, 100.0);
+    }
+  }
+  for event in motion.read() {
+    if buttons.pressed(MouseButton::Right) && !over_ui {
+      transform.translation.x -= event.delta.x * projection.scale;
+      transform.translation.y += event.delta.y * projection.scale;
+    }
+  }
+}
+
+fn edit_level(
+  mut state: ResMut<EditorState>,
+  mut contexts: EguiContexts,
+  buttons: Res<Input<MouseButton>>,
+  keyboard_input: Res<Input<KeyCode>>,
+  qry_window: Query<&Window, With<PrimaryWindow>>,
+  qry_cam: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<EditorCamera>>,
+) {
+  let ctx = contexts.ctx_mut();
+  if keyboard_input.just_pressed(KeyCode::Delete) && !ctx.wants_keyboard_input() {
+    state.delete_selected();
+  }
+  let Ok((camera, cam_transform, projection)) = qry_cam.get_single() else {
+    return;
+  };
+  let Some(cursor) = qry_window
+    .get_single()
+    .ok()
+    .and_then(|w| w.cursor_position())
+    .and_then(|p| camera.viewport_to_world_2d(cam_transform, p))
+  else {
+    return;
+  };
+
+  if buttons.just_pressed(MouseButton::Left) && !ctx.wants_pointer_input() {
+    let radius = HANDLE_RADIUS * projection.scale;
+    state.selected = state
+      .handles()
+      .into_iter()
+      .map(|(grab, p)| (grab, p.distance(cursor)))
+      .filter(|(_, d)| *d < radius)
+      .min_by(|a, b| a.1.total_cmp(&b.1))
+      .map(|(grab, _)| grab);
+    state.dragging = state.selected.is_some();
+  }
+  if !buttons.pressed(MouseButton::Left) {
+    state.dragging = false;
+  }
+  if let (true, Some(grab)) = (state.dragging, state.selected) {
+    state.move_grab(grab, cursor);
+  }
+}
+
+fn draw_level(
+  state: Res<EditorState>,
+  mut gizmos: Gizmos,
+  qry_cam: Query<&OrthographicProjection, With<EditorCamera>>,
+) {
+  let scale = qry_cam.get_single().map_or(1.0, |p| p.scale);
+  let handle_size = HANDLE_RADIUS * scale * 0.5;
+
+  for piece in state.pieces.iter() {
+    let color = if piece.wall { Color::RED } else { Color::GREEN };
+    match piece.shape {
+      PieceShape::Box { half_size } => gizmos.rect_2d(piece.center, 0.0, half_size * 2.0, color),
+      PieceShape::Triangle([a, b, c]) => {
+        let c0 = piece.center;
+        gizmos.line_2d(c0 + a, c0 + b, color);
+        gizmos.line_2d(c0 + b, c0 + c, color);
+        gizmos.line_2d(c0 + c, c0 + a, color);
+      }
+    }
+  }
+  let finish = state.level.finish_bounds;
+  gizmos.rect_2d(finish.xy(), 0.0, finish.zw() * 2.0, Color::GOLD);
+  for p in state.level.spawn_points.iter() {
+    gizmos.circle_2d(*p, handle_size * 2.0, Color::CYAN);
+  }
+  gizmos.circle_2d(state.level.starting_point, handle_size * 2.0, Color::WHITE);
+
+  for (grab, p) in state.handles() {
+    let color = if state.selected == Some(grab) {
+      Color::YELLOW
+    } else {
+      Color::GRAY
+    };
+    gizmos.rect_2d(p, 0.0, Vec2::splat(handle_size), color);
+  }
+}
+
+fn optional_value<T: egui::emath::Numeric>(
+  ui: &mut egui::Ui,
+  label: &str,
+  value: &mut Option<T>,
+  default: T,
+) {
+  ui.horizontal(|ui| {
+    let mut enabled = value.is_some();
+    ui.checkbox(&mut enabled, label);
+    if enabled {
+      ui.add(egui::DragValue::new(value.get_or_insert(default)));
+    } else {
+      *value = None;
+    }
+  });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor_panel<T: States + Copy>(
+  mut contexts: EguiContexts,
+  mut state: ResMut<EditorState>,
+  mut shaders: ResMut<Assets<Shader>>,
+  asset_server: Res<AssetServer>,
+  mut lvl_reg: ResMut<LevelRegistry>,
+  mut lvl_mgr: ResMut<LevelManager>,
+  mut cmds: EventWriter<GameControlCommand>,
+  states: Res<EditorStates<T>>,
+  mut app_state: ResMut<NextState<T>>,
+  qry_cam: Query<&Transform, With<EditorCamera>>,
+) {
+  let view_center = qry_cam.get_single().map_or(Vec2::ZERO, |t| t.translation.xy());
+  let mut play = false;
+  let state = &mut *state;
+
+  egui::Window::new("Level Editor").show(contexts.ctx_mut(), |ui| {
+    ui.horizontal(|ui| {
+      ui.label("Id");
+      ui.text_edit_singleline(&mut state.id);
+    });
+    ui.horizontal(|ui| {
+      ui.label("Name");
+      ui.text_edit_singleline(&mut state.level.name);
+    });
+    ui.horizontal(|ui| {
+      ui.label("Boids per spawn point");
+      ui.add(egui::DragValue::new(&mut state.level.boids_per_spawn_point).clamp_range(0..=500));
+    });
+    optional_value(ui, "Rescue goal", &mut state.level.rescue_goal, 10);
+    optional_value(ui, "Time goal (s)", &mut state.level.time_goal, 120.0);
+    ui.checkbox(&mut state.level.wander, "Wandering boids");
+    ui.horizontal(|ui| {
+      ui.label("Wall avoidance");
+      let avoidance = &mut state.level.wall_avoidance;
+      ui.radio_value(avoidance, WallAvoidance::Probes, "Probes");
+      ui.radio_value(avoidance, WallAvoidance::Gradient, "Gradient");
+    });
+    ui.horizontal(|ui| {
+      ui.label("Walls");
+      let collision = &mut state.level.collision;
+      ui.radio_value(collision, CollisionResponse::Stop, "Deadly");
+      ui.radio_value(collision, CollisionResponse::Reflect, "Bouncy");
+      ui.radio_value(collision, CollisionResponse::Slide, "Sliding");
+    });
+
+    ui.separator();
+    ui.horizontal(|ui| {
+      if ui.button("Add box").clicked() {
+        state.pieces.push(Piece {
+          center: view_center,
+          shape: PieceShape::Box {
+            half_size: Vec2::splat(500.0),
+          },
+          wall: false,
+        });
+      }
+      if ui.button("Add triangle").clicked() {
+        state.pieces.push(Piece {
+          center: view_center,
+          shape: PieceShape::Triangle([
+            Vec2::new(-500.0, -500.0),
+            Vec2::new(500.0, -500.0),
+            Vec2::new(0.0, 500.0),
+          ]),
+          wall: false,
+        });
+      }
+      if ui.button("Add spawn point").clicked() {
+        state.level.spawn_points.push(view_center);
+      }
+    });
+    if let Some(Grab::Piece(i) | Grab::BoxCorner(i) | Grab::Vertex(i, _)) = state.selected {
+      ui.checkbox(&mut state.pieces[i].wall, "Selected piece is a wall");
+    }
+    if state.selected.is_some() && ui.button("Delete selected").clicked() {
+      state.delete_selected();
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+      ui.label("File");
+      ui.text_edit_singleline(&mut state.export_path);
+    });
+    ui.horizontal(|ui| {
+      if ui.button("Load").clicked() {
+        let path = state.export_path.clone();
+        state.status = match state.load(&path) {
+          Ok(()) => format!("loaded {path}"),
+          Err(e) => e,
+        };
+      }
+      if ui.button("Export").clicked() {
+        state.status = match state.export() {
+          Ok(()) => format!("exported to {}", state.export_path),
+          Err(e) => e,
+        };
+      }
+      play = ui.button("Play-test").clicked();
+    });
+    ui.label(state.status.as_str());
+    ui.small("left drag: move handles, right drag: pan, scroll: zoom, esc: back to title");
+  });
+
+  if !play {
+    return;
+  }
+  let file = match state.build() {
+    Ok(file) => file,
+    Err(e) => {
+      state.status = e;
+      return;
+    }
+  };
+  if let Some(previous) = state.playtest_sdf.take() {
+    shaders.remove(&previous);
+  }
+  state.playtests += 1;
+  let sdf = shaders.add(
+    file
+      .bounds_shape()
+      .shader(&format!("smud::editor_playtest_{}", state.playtests)),
+  );
+  state.playtest_sdf = Some(sdf.clone());
+  let fill = asset_server.load(file.terrain.fill.clone());
+  let mut level = file.into_level(sdf, fill, Vec::new());
+
+  // the play-test level is replaced every time, completing it starts it again
+  let id = playtest_id();
+  level.next_level = Some(id.clone());
+  lvl_reg.replace(id.clone(), level);
+  lvl_mgr.unlocked.insert(id.clone());
+  cmds.send(GameControlCommand::StartLevel(id));
+  app_state.set(states.game);
+  state.status.clear();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn open_box(center: Vec2, half_size: Vec2) -> Piece {
+    Piece {
+      center,
+      shape: PieceShape::Box { half_size },
+      wall: false,
+    }
+  }
+
+  fn triangle(center: Vec2, wall: bool) -> Piece {
+    Piece {
+      center,
+      shape: PieceShape::Triangle([
+        Vec2::new(-600.0, -300.0),
+        Vec2::new(600.0, -300.0),
+        Vec2::new(0.0, 600.0),
+      ]),
+      wall,
+    }
+  }
+
+  #[test]
+  fn bounds_frame_the_open_pieces() {
+    let state = EditorState {
+      pieces: vec![open_box(Vec2::new(0.0, 1000.0), Vec2::new(500.0, 2000.0))],
+      ..default()
+    };
+    let frame = Shape::rect(
+      Vec2::new(0.0, 1000.0),
+      Vec2::new(500.0, 2000.0) + FRAME_MARGIN,
+    );
+    let playable = Shape::Union(vec![Shape::rect(
+      Vec2::new(0.0, 1000.0),
+      Vec2::new(500.0, 2000.0),
+    )]);
+    assert_eq!(state.bounds(), Ok(frame.subtract(playable)));
+  }
+
+  #[test]
+  fn bounds_cut_walls_out_of_the_open_pieces() {
+    let state = EditorState {
+      pieces: vec![
+        open_box(Vec2::ZERO, Vec2::splat(2000.0)),
+        triangle(Vec2::ZERO, true),
+      ],
+      ..default()
+    };
+    let bounds = state.bounds().unwrap();
+    // in the open box, in the wall and outside of everything
+    assert!(bounds.distance(Vec2::new(1500.0, 1500.0)) > 0.0);
+    assert!(bounds.distance(Vec2::ZERO) < 0.0);
+    assert!(bounds.distance(Vec2::new(3000.0, 0.0)) < 0.0);
+  }
+
+  #[test]
+  fn bounds_need_an_open_piece() {
+    let state = EditorState {
+      pieces: vec![triangle(Vec2::ZERO, true)],
+      ..default()
+    };
+    assert!(state.bounds().is_err());
+  }
+
+  #[test]
+  fn move_grab_moves_and_resizes() {
+    let mut state = EditorState {
+      pieces: vec![
+        open_box(Vec2::ZERO, Vec2::splat(500.0)),
+        triangle(Vec2::ZERO, false),
+      ],
+      ..default()
+    };
+    state.move_grab(Grab::Piece(0), Vec2::new(100.0, 200.0));
+    assert_eq!(state.pieces[0].center, Vec2::new(100.0, 200.0));
+    state.move_grab(Grab::BoxCorner(0), Vec2::new(-300.0, 800.0));
+    assert_eq!(
+      state.pieces[0].shape,
+      PieceShape::Box {
+        half_size: Vec2::new(400.0, 600.0)
+      }
+    );
+    // boxes never collapse
+    state.move_grab(Grab::BoxCorner(0), Vec2::new(100.0, 200.0));
+    assert_eq!(
+      state.pieces[0].shape,
+      PieceShape::Box {
+        half_size: Vec2::ONE
+      }
+    );
+    state.move_grab(Grab::Vertex(1, 2), Vec2::new(50.0, 900.0));
+    assert_eq!(state.pieces[1].points()[2], Vec2::new(50.0, 900.0));
+    state.move_grab(Grab::FinishCorner, Vec2::new(-200.0, 6000.0));
+    assert_eq!(
+      state.level.finish_bounds,
+      Vec4::new(0.0, 5500.0, 200.0, 500.0)
+    );
+    state.move_grab(Grab::Spawn(0), Vec2::new(10.0, 20.0));
+    assert_eq!(state.level.spawn_points, vec![Vec2::new(10.0, 20.0)]);
+  }
+
+  #[test]
+  fn delete_selected_removes_pieces_and_spawn_points() {
+    let mut state = EditorState {
+      pieces: vec![
+        open_box(Vec2::ZERO, Vec2::splat(500.0)),
+        triangle(Vec2::ZERO, false),
+      ],
+      selected: Some(Grab::BoxCorner(0)),
+      ..default()
+    };
+    state.delete_selected();
+    assert_eq!(state.pieces, vec![triangle(Vec2::ZERO, false)]);
+    assert_eq!(state.selected, None);
+
+    state.selected = Some(Grab::Spawn(0));
+    state.delete_selected();
+    assert!(state.level.spawn_points.is_empty());
+
+    // the start and finish can't be deleted
+    state.selected = Some(Grab::Start);
+    state.delete_selected();
+    assert_eq!(state.level.starting_point, Vec2::new(0.0, -5000.0));
+  }
+
+  #[test]
+  fn exported_levels_open_again() {
+    let state = EditorState {
+      pieces: vec![
+        open_box(Vec2::new(0.0, 1000.0), Vec2::new(2000.0, 6000.0)),
+        triangle(Vec2::new(1000.0, 0.0), true),
+      ],
+      id: "custom:reopened".to_owned(),
+      ..default()
+    };
+    let file = state.build().unwrap();
+
+    let mut reopened = EditorState::default();
+    reopened.open(file).unwrap();
+    assert_eq!(reopened.id, "custom:reopened");
+    assert_eq!(reopened.pieces, state.pieces);
+    assert_eq!(reopened.build().unwrap().bounds, state.bounds().unwrap());
+  }
+
+  #[test]
+  fn base_game_level_files_open() {
+    let mut state = EditorState::default();
+    state.load("assets/levels/level3.level.ron").unwrap();
+    assert_eq!(state.id, "base_game:level3");
+    assert_eq!(
+      state.pieces,
+      vec![open_box(Vec2::ZERO, Vec2::new(500.0, 10000.0))]
+    );
+  }
+
+  #[test]
+  fn other_shapes_are_not_opened() {
+    let mut file = EditorState::default().build().unwrap();
+    file.bounds = Shape::Circle(5000.0).subtract(Shape::Circle(1000.0));
+    let mut state = EditorState::default();
+    assert!(state.open(file).is_err());
+    assert_eq!(state.pieces, EditorState::default().pieces);
+  }
+}
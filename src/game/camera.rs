@@ -39,5 +39,5 @@ pub fn setup_camera(mut cmd: Commands) {
     ..default()
   };
   cam.projection.scaling_mode = ScalingMode::FixedVertical(2000.0);
-  cmd.spawn((cam, BloomSettings::default()));
+  cmd.spawn((cam, BloomSettings::default(), PlayerCamera));
 }
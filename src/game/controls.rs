@@ -1,12 +1,18 @@
 use bevy::prelude::*;
 use bevy_hanabi::ParticleEffect;
 use jam4::{
-  boid::{Boid, BoidConfig, TamedBoid},
+  boid::{Boid, TamedBoid},
   level::{LevelManager, LevelRegistry},
-  Player, PlayerInfo,
+  replay::{Replay, ReplayCommand},
+  GameControlCommand, Player, PlayerInfo, PlayerInput, SimulationState,
 };
 
-use crate::jukebox::{BgMusic, MusicCommand};
+use crate::{
+  input::{Action, ActionState},
+  jukebox::{BgMusic, MusicCommand},
+};
+
+const REPLAY_FILE: &str = "replay.ron";
 
 #[derive(Component)]
 pub struct InPlayingScreen;
@@ -88,10 +94,9 @@ pub fn update_player_ui(
   let Ok(mut txt_time) = qry_time.get_single_mut() else {
     return;
   };
-  let Some(level_id) = lvl_mgr.current_level else {
+  let Ok(lvl) = lvl_mgr.current(&lvl_reg) else {
     return;
   };
-  let lvl = lvl_reg.get_level(&level_id);
 
   let tamed = qry_boid.iter().count();
   if let Some(rescue_target) = lvl.rescue_goal {
@@ -109,64 +114,66 @@ pub fn update_player_ui(
   }
 }
 
-pub fn calc_player_direction(
-  mut qry: Query<&mut Boid, With<Player>>,
+pub fn read_player_input(actions: Res<ActionState>, mut input: ResMut<PlayerInput>) {
+  input.turn = actions.turn;
+  input.boost = actions.pressed(Action::Boost);
+}
+
+/// F5 saves the current run, F9 retries the level with the saved run as input
+pub fn replay_hotkeys(
   keyboard_input: Res<Input<KeyCode>>,
-  time: Res<Time>,
+  mut replay_cmds: EventWriter<ReplayCommand>,
+  mut game_cmds: EventWriter<GameControlCommand>,
+  sim_state: Res<State<SimulationState>>,
 ) {
-  if let Ok(mut p) = qry.get_single_mut() {
-    let mut turning_force = Vec2::ZERO;
-
-    if keyboard_input.pressed(KeyCode::A) {
-      turning_force += Mat2::from_angle(90.0f32.to_radians()).mul_vec2(p.direction);
-    } else if keyboard_input.pressed(KeyCode::D) {
-      turning_force += Mat2::from_angle(-90.0f32.to_radians()).mul_vec2(p.direction);
+  if keyboard_input.just_pressed(KeyCode::F5) {
+    replay_cmds.send(ReplayCommand::Save(REPLAY_FILE.to_owned()));
+  }
+  if keyboard_input.just_pressed(KeyCode::F9)
+    && matches!(sim_state.get(), SimulationState::GameOver(_))
+  {
+    match Replay::load(REPLAY_FILE) {
+      Ok(replay) => {
+        replay_cmds.send(ReplayCommand::Play(replay));
+        game_cmds.send(GameControlCommand::Retry);
+      }
+      Err(e) => error!("failed to load replay from {REPLAY_FILE}: {e}"),
     }
-
-    p.direction =
-      (p.direction + (turning_force * time.delta_seconds() * p.turning_speed)).normalize();
   }
 }
 
+/// Only presentation, the simulation switches modes in `steer_player`
 pub fn toggle_player_mode(
   mut cmd: Commands,
-  mut qry: Query<(&mut ParticleEffect, &mut Boid, &mut Handle<ColorMaterial>), With<Player>>,
-  keyboard_input: Res<Input<KeyCode>>,
-  mut player: ResMut<PlayerInfo>,
-  bconfig: Res<BoidConfig>,
+  mut qry: Query<(&mut ParticleEffect, &mut Handle<ColorMaterial>), With<Player>>,
+  player: Res<PlayerInfo>,
   mut qry_music: Query<&mut AudioSink, With<BgMusic>>,
 ) {
-  let Ok((mut fx, mut boid, mut mat)) = qry.get_single_mut() else {
+  let Ok((mut fx, mut mat)) = qry.get_single_mut() else {
     return;
   };
+  if (*mat == player.boost_color) == player.in_boost_mode {
+    return;
+  }
 
-  if keyboard_input.just_pressed(KeyCode::Space) {
-    player.in_boost_mode = true;
-
+  if player.in_boost_mode {
     cmd.spawn(AudioBundle {
       source: player.audio_boost.clone(),
       settings: PlaybackSettings::DESPAWN,
     });
     fx.handle = player.boost_particles.clone();
     *mat = player.boost_color.clone();
-    boid.speed = bconfig.min_speed;
-    boid.turning_speed = bconfig.min_turn_speed;
 
     for m in qry_music.iter_mut() {
       m.set_speed(0.98);
     }
-  }
-
-  if keyboard_input.just_released(KeyCode::Space) {
-    player.in_boost_mode = false;
+  } else {
     cmd.spawn(AudioBundle {
       source: player.audio_slow.clone(),
       settings: PlaybackSettings::DESPAWN,
     });
     fx.handle = player.normal_particles.clone();
     *mat = player.normal_color.clone();
-    boid.speed = bconfig.max_speed;
-    boid.turning_speed = bconfig.max_turn_speed;
 
     for m in qry_music.iter_mut() {
       m.set_speed(1.0);
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use jam4::{GameControlCommand, SimulationErrors};
+use utils::text::TextAnimation;
+
+use utils::colors::*;
+
+use crate::{
+  input::{Action, ActionState, InputBindings},
+  jukebox::{BgMusic, MusicCommand},
+};
+
+use super::GameNextState;
+
+#[derive(Component)]
+pub struct InErrorScreen;
+
+/// Back to the title, the level select only offers levels that exist
+pub fn wait_to_quit_on_error<T: States + Copy>(
+  mut cmds: EventWriter<GameControlCommand>,
+  mut music: EventWriter<MusicCommand>,
+  mut app_state: ResMut<NextState<T>>,
+  current_state: Res<State<T>>,
+  next_state: Res<GameNextState<T>>,
+  actions: Res<ActionState>,
+) {
+  if !actions.just_pressed(Action::Confirm) {
+    return;
+  }
+  cmds.send(GameControlCommand::Quit);
+  // initialization errors happen on the title screen already
+  if *current_state.get() != next_state.0 {
+    music.send(MusicCommand::Play(BgMusic::Menu));
+    app_state.set(next_state.0);
+  }
+}
+
+pub fn on_error(mut cmd: Commands, errors: Res<SimulationErrors>, bindings: Res<InputBindings>) {
+  cmd
+    .spawn((
+      NodeBundle {
+        style: Style {
+          align_items: AlignItems::Center,
+          justify_content: JustifyContent::Center,
+          width: Val::Percent(100.0),
+          height: Val::Percent(100.0),
+          display: Display::Flex,
+          flex_direction: FlexDirection::Column,
+          ..default()
+        },
+        background_color: BackgroundColor(RAISIN.with_a(0.9)),
+        ..default()
+      },
+      InErrorScreen,
+    ))
+    .with_children(|parent| {
+      parent.spawn(
+        TextBundle::from_section(
+          errors.0.join("\n"),
+          TextStyle {
+            font_size: 24.0,
+            color: LILAC,
+            ..default()
+          },
+        )
+        .with_text_alignment(TextAlignment::Center),
+      );
+      parent
+        .spawn(
+          TextBundle::from_section(
+            "",
+            TextStyle {
+              font_size: 30.0,
+              color: MISTY,
+              ..default()
+            },
+          )
+          .with_text_alignment(TextAlignment::Center)
+          .with_style(Style {
+            margin: UiRect::top(Val::Px(50.0)),
+            ..default()
+          }),
+        )
+        .insert(TextAnimation {
+          text: format!(
+            "Something went wrong\npress {} to go back",
+            bindings.key_name(Action::Confirm)
+          ),
+          animation_speed: 1.0,
+        });
+    });
+}
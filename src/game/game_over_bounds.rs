@@ -4,22 +4,43 @@ use utils::text::TextAnimation;
 
 use utils::colors::*;
 
-use crate::jukebox::{MusicCommand, BgMusic};
+use crate::{
+  input::{Action, ActionState, InputBindings},
+  jukebox::{BgMusic, MusicCommand},
+};
 
 #[derive(Component)]
 pub struct InGameOverScreen;
 
 pub fn wait_to_retry(
   mut cmds: EventWriter<GameControlCommand>,
-  keyboard_input: Res<Input<KeyCode>>,
+  actions: Res<ActionState>,
 ) {
-  if keyboard_input.just_pressed(KeyCode::Space) {
+  if actions.just_pressed(Action::Retry) {
     cmds.send(GameControlCommand::Retry);
   }
 }
 
-pub fn on_game_over_bounds(mut cmd: Commands, mut cmds: EventWriter<MusicCommand>,) {
+pub fn on_game_over_bounds(
+  mut cmd: Commands,
+  mut cmds: EventWriter<MusicCommand>,
+  bindings: Res<InputBindings>,
+) {
   cmds.send(MusicCommand::Play(BgMusic::GameOver));
+  spawn_game_over_screen(&mut cmd, &bindings, None);
+}
+
+/// `reason` explains which goal was missed, without one the screen only offers a retry
+pub fn spawn_game_over_screen(
+  cmd: &mut Commands,
+  bindings: &InputBindings,
+  reason: Option<String>,
+) {
+  let retry = format!("press {} to retry", bindings.key_name(Action::Retry));
+  let text = match reason {
+    Some(reason) => format!("Game Over\n{reason}\n{retry}"),
+    None => format!("Game Over\n{retry}"),
+  };
   cmd
     .spawn((
       NodeBundle {
@@ -55,7 +76,7 @@ pub fn on_game_over_bounds(mut cmd: Commands, mut cmds: EventWriter<MusicCommand
           }),
         )
         .insert(TextAnimation {
-          text: "Game Over\npress space to retry".to_owned(),
+          text,
           animation_speed: 1.0,
         });
     });
@@ -1,66 +1,27 @@
 use bevy::prelude::*;
 use jam4::level::{LevelManager, LevelRegistry};
-use utils::text::TextAnimation;
 
-use utils::colors::*;
+use crate::{
+  input::InputBindings,
+  jukebox::{BgMusic, MusicCommand},
+};
 
-use crate::jukebox::{BgMusic, MusicCommand};
-
-use super::game_over_bounds::InGameOverScreen;
+use super::game_over_bounds::spawn_game_over_screen;
 
 pub fn on_game_over_time(
   mut cmd: Commands,
   mut cmds: EventWriter<MusicCommand>,
   lvl_mgr: ResMut<LevelManager>,
   lvl_reg: Res<LevelRegistry>,
+  bindings: Res<InputBindings>,
 ) {
-  let Some(level_id) = lvl_mgr.current_level else {
-    return;
-  };
-  let lvl = lvl_reg.get_level(&level_id);
-  let target = lvl.time_goal.expect("should have a time goal");
+  // a level without the goal should not end up here, the generic screen still allows a retry
+  let reason = lvl_mgr
+    .current(&lvl_reg)
+    .ok()
+    .and_then(|lvl| lvl.time_goal)
+    .map(|target| format!("you need to clear the level in {}s", target.as_secs()));
 
   cmds.send(MusicCommand::Play(BgMusic::GameOver));
-  cmd
-    .spawn((
-      NodeBundle {
-        style: Style {
-          align_items: AlignItems::Center,
-          justify_content: JustifyContent::Center,
-          width: Val::Percent(100.0),
-          height: Val::Percent(100.0),
-          display: Display::Flex,
-          flex_direction: FlexDirection::Column,
-          ..default()
-        },
-        background_color: BackgroundColor(RAISIN.with_a(0.8)),
-        ..default()
-      },
-      InGameOverScreen,
-    ))
-    .with_children(|parent| {
-      parent
-        .spawn(
-          TextBundle::from_section(
-            "",
-            TextStyle {
-              font_size: 30.0,
-              color: MISTY,
-              ..default()
-            },
-          )
-          .with_text_alignment(TextAlignment::Center)
-          .with_style(Style {
-            margin: UiRect::top(Val::Px(50.0)),
-            ..default()
-          }),
-        )
-        .insert(TextAnimation {
-          text: format!(
-            "Game Over\nyou need to clear the level in {}s\npress space to retry",
-            target.as_secs()
-          ),
-          animation_speed: 1.0,
-        });
-    });
+  spawn_game_over_screen(&mut cmd, &bindings, reason);
 }
@@ -44,7 +44,7 @@ pub fn on_game_complete(mut cmd: Commands, player: Res<PlayerInfo>) {
         )
         .insert(TextAnimation {
           text: format!(
-            "GG\nYou rescued {} triangles\nThank you for playing!",
+            "GG\nFinal score {}\nThank you for playing!",
             player.score
           ),
           animation_speed: 1.0,
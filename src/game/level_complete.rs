@@ -1,10 +1,13 @@
 use bevy::prelude::*;
 use jam4::{
   level::{LevelManager, LevelRegistry},
-  GameControlCommand, PlayerInfo,
+  scoring::LevelScore,
+  GameControlCommand, PlayerInfo, SimulationFailed,
 };
 use utils::text::TextAnimation;
 
+use crate::input::{Action, ActionState, InputBindings};
+
 use utils::colors::*;
 
 #[derive(Component)]
@@ -12,9 +15,9 @@ pub struct InLevelCompleteScreen;
 
 pub fn wait_to_next_level(
   mut cmds: EventWriter<GameControlCommand>,
-  keyboard_input: Res<Input<KeyCode>>,
+  actions: Res<ActionState>,
 ) {
-  if keyboard_input.just_pressed(KeyCode::Space) {
+  if actions.just_pressed(Action::Confirm) {
     cmds.send(GameControlCommand::NextLevel);
   }
 }
@@ -23,14 +26,24 @@ pub fn setup_level_complete(
   mut cmd: Commands,
   lvl_mgr: ResMut<LevelManager>,
   lvl_reg: Res<LevelRegistry>,
-  mut cmds: EventWriter<GameControlCommand>,
   player: Res<PlayerInfo>,
+  score: Res<LevelScore>,
+  bindings: Res<InputBindings>,
+  mut failed: EventWriter<SimulationFailed>,
 ) {
-  let level_id = lvl_mgr.current_level.unwrap();
-  let lvl = lvl_reg.get_level(&level_id);
-  let Some(_nxt_level_id) = lvl.next_level else {
-    cmds.send(GameControlCommand::NextLevel);
-    return;
+  let lvl = match lvl_mgr.current(&lvl_reg) {
+    Ok(lvl) => lvl,
+    Err(e) => {
+      failed.send(e.into());
+      return;
+    }
+  };
+  // the last level still gets its score, there is just nothing to continue to
+  let key = bindings.key_name(Action::Confirm);
+  let prompt = if lvl.next_level.is_some() {
+    format!("Press {key} to continue")
+  } else {
+    format!("Press {key} to finish")
   };
   cmd
     .spawn((
@@ -68,8 +81,23 @@ pub fn setup_level_complete(
         )
         .insert(TextAnimation {
           text: format!(
-            "{} complete\nYou have rescued {} shapes so far\nPress space to continue",
-            lvl.name, player.score
+            "{} complete  {}\n\
+             rescued {} shapes  +{}\n\
+             time bonus  +{}\n\
+             shapes lost  -{}\n\
+             boost used  -{}\n\
+             level score {}  total score {}\n\
+             {}",
+            lvl.name,
+            "*".repeat(score.stars as usize),
+            score.rescued,
+            score.rescue_points,
+            score.time_bonus,
+            score.lost_penalty,
+            score.boost_penalty,
+            score.total,
+            player.score,
+            prompt
           ),
           animation_speed: 1.0,
         });
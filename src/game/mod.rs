@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{input::InputSystem, prelude::*};
 
 use camera::*;
 
@@ -7,36 +7,45 @@ use utils::despawn_screen;
 
 use self::{
   controls::{
-    calc_player_direction, setup_player_ui, toggle_player_mode, update_player_ui, InPlayingScreen,
+    read_player_input, replay_hotkeys, setup_player_ui, toggle_player_mode, update_player_ui,
+    InPlayingScreen,
   },
+  error::{on_error, wait_to_quit_on_error, InErrorScreen},
   game_over_boid::on_game_over_boid,
   game_over_bounds::{on_game_over_bounds, wait_to_retry, InGameOverScreen},
   game_over_time::on_game_over_time,
   gg::on_game_complete,
   level_complete::{setup_level_complete, wait_to_next_level, InLevelCompleteScreen},
+  pause::{pause_menu, setup_pause_menu, wait_to_pause, InPauseScreen, PauseMenu},
 };
 
 mod controls;
+mod error;
 mod game_over_boid;
 mod game_over_bounds;
 mod game_over_time;
 mod gg;
 mod level_complete;
+mod pause;
 
 mod camera;
 #[cfg(feature = "debug")]
 mod debug;
 
+/// State to go to when quitting from the pause menu
 #[derive(Resource)]
-struct GameNextState<T>(T);
+pub struct GameNextState<T>(pub T);
 pub trait GameExtensions {
-  fn add_game<T: States + Copy>(&mut self, game_state: T) -> &mut Self;
+  fn add_game<T: States + Copy>(&mut self, game_state: T, title_state: T) -> &mut Self;
 }
 
 impl GameExtensions for App {
-  fn add_game<T: States + Copy>(&mut self, game_state: T) -> &mut Self {
+  fn add_game<T: States + Copy>(&mut self, game_state: T, title_state: T) -> &mut Self {
     self
+      .insert_resource(GameNextState(title_state))
+      .init_resource::<PauseMenu>()
       .add_systems(OnEnter(game_state), setup_camera)
+      .add_systems(OnExit(game_state), despawn_screen::<PlayerCamera>)
       .add_systems(OnEnter(SimulationState::GameComplete), on_game_complete)
       .add_systems(
         OnExit(SimulationState::GameComplete),
@@ -74,21 +83,46 @@ impl GameExtensions for App {
         OnExit(SimulationState::LevelComplete),
         despawn_screen::<InLevelCompleteScreen>,
       )
-      .add_systems(OnEnter(SimulationState::Simulating), setup_player_ui)
+      // the player UI stays up while paused
+      .add_systems(
+        OnTransition {
+          from: SimulationState::ChoosingLevel,
+          to: SimulationState::Simulating,
+        },
+        setup_player_ui,
+      )
       .add_systems(
         OnExit(SimulationState::Simulating),
-        despawn_screen::<InPlayingScreen>,
+        despawn_screen::<InPlayingScreen>.run_if(not(in_state(SimulationState::Paused))),
+      )
+      .add_systems(OnEnter(SimulationState::Error), on_error)
+      .add_systems(OnExit(SimulationState::Error), despawn_screen::<InErrorScreen>)
+      .add_systems(OnEnter(SimulationState::Paused), setup_pause_menu)
+      .add_systems(
+        OnExit(SimulationState::Paused),
+        (
+          despawn_screen::<InPauseScreen>,
+          despawn_screen::<InPlayingScreen>.run_if(not(in_state(SimulationState::Simulating))),
+        ),
+      )
+      .add_systems(
+        PreUpdate,
+        read_player_input
+          .after(InputSystem)
+          .run_if(in_state(SimulationState::Simulating)),
       )
       .add_systems(
         Update,
         (
+          replay_hotkeys,
           (
-            calc_player_direction,
             toggle_player_mode,
             follow_player,
             update_player_ui,
+            wait_to_pause,
           )
             .run_if(in_state(SimulationState::Simulating)),
+          pause_menu::<T>.run_if(in_state(SimulationState::Paused)),
           wait_to_retry.run_if(in_state(SimulationState::GameOver(
             jam4::GameOverReason::OutOfBoids,
           ))),
@@ -99,6 +133,7 @@ impl GameExtensions for App {
             jam4::GameOverReason::OutOfBounds,
           ))),
           wait_to_next_level.run_if(in_state(SimulationState::LevelComplete)),
+          wait_to_quit_on_error::<T>.run_if(in_state(SimulationState::Error)),
         ),
       );
     #[cfg(feature = "debug")]
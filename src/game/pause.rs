@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use jam4::GameControlCommand;
+
+use utils::colors::*;
+
+use crate::{
+  input::{Action, ActionState},
+  jukebox::{BgMusic, MusicCommand},
+};
+
+use super::GameNextState;
+
+#[derive(Component)]
+pub struct InPauseScreen;
+
+#[derive(Component)]
+pub struct PauseMenuItem(usize);
+
+const MENU_ITEMS: [&str; 3] = ["Resume", "Restart level", "Quit to title"];
+
+#[derive(Resource, Default)]
+pub struct PauseMenu {
+  selected: usize,
+}
+
+pub fn wait_to_pause(mut cmds: EventWriter<GameControlCommand>, actions: Res<ActionState>) {
+  if actions.just_pressed(Action::Pause) {
+    cmds.send(GameControlCommand::Pause);
+  }
+}
+
+pub fn setup_pause_menu(mut cmd: Commands, mut menu: ResMut<PauseMenu>) {
+  menu.selected = 0;
+  cmd
+    .spawn((
+      NodeBundle {
+        style: Style {
+          align_items: AlignItems::Center,
+          justify_content: JustifyContent::Center,
+          width: Val::Percent(100.0),
+          height: Val::Percent(100.0),
+          display: Display::Flex,
+          flex_direction: FlexDirection::Column,
+          ..default()
+        },
+        background_color: BackgroundColor(RAISIN.with_a(0.8)),
+        ..default()
+      },
+      InPauseScreen,
+    ))
+    .with_children(|parent| {
+      parent.spawn(
+        TextBundle::from_section(
+          "Paused",
+          TextStyle {
+            font_size: 60.0,
+            color: MISTY,
+            ..default()
+          },
+        )
+        .with_style(Style {
+          margin: UiRect::bottom(Val::Px(30.0)),
+          ..default()
+        }),
+      );
+      for (i, item) in MENU_ITEMS.iter().enumerate() {
+        parent
+          .spawn(
+            TextBundle::from_section(
+              *item,
+              TextStyle {
+                font_size: 30.0,
+                color: MISTY,
+                ..default()
+              },
+            )
+            .with_style(Style {
+              margin: UiRect::top(Val::Px(10.0)),
+              ..default()
+            }),
+          )
+          .insert(PauseMenuItem(i));
+      }
+    });
+}
+
+pub fn pause_menu<T: States + Copy>(
+  actions: Res<ActionState>,
+  mut menu: ResMut<PauseMenu>,
+  mut cmds: EventWriter<GameControlCommand>,
+  mut music_cmds: EventWriter<MusicCommand>,
+  mut app_state: ResMut<NextState<T>>,
+  title_state: Res<GameNextState<T>>,
+  mut qry: Query<(&mut Text, &PauseMenuItem)>,
+) {
+  if actions.just_pressed(Action::Pause) {
+    cmds.send(GameControlCommand::Resume);
+    return;
+  }
+  if actions.just_pressed(Action::MenuUp) {
+    menu.selected = (menu.selected + MENU_ITEMS.len() - 1) % MENU_ITEMS.len();
+  }
+  if actions.just_pressed(Action::MenuDown) {
+    menu.selected = (menu.selected + 1) % MENU_ITEMS.len();
+  }
+
+  for (mut text, item) in qry.iter_mut() {
+    let color = if item.0 == menu.selected { FAIRY } else { MISTY };
+    for section in text.sections.iter_mut() {
+      section.style.color = color;
+    }
+  }
+
+  if actions.just_pressed(Action::Confirm) {
+    match menu.selected {
+      0 => cmds.send(GameControlCommand::Resume),
+      1 => cmds.send(GameControlCommand::Retry),
+      _ => {
+        cmds.send(GameControlCommand::Quit);
+        music_cmds.send(MusicCommand::Play(BgMusic::Menu));
+        app_state.set(title_state.0);
+      }
+    }
+  }
+}
@@ -0,0 +1,287 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+const BINDINGS_FILE: &str = "bindings.ron";
+/// folder inside the platform config dir, e.g. `~/.config` on linux
+#[cfg(not(target_arch = "wasm32"))]
+const CONFIG_DIR: &str = "triangle-apocalypse";
+#[cfg(target_arch = "wasm32")]
+const BINDINGS_KEY: &str = "triangle-apocalypse-bindings";
+
+pub trait InputExtensions {
+  fn add_input_actions(&mut self) -> &mut Self;
+}
+
+impl InputExtensions for App {
+  fn add_input_actions(&mut self) -> &mut Self {
+    self
+      .insert_resource(InputBindings::load())
+      .init_resource::<ActionState>()
+      .add_systems(PreUpdate, update_action_state.after(InputSystem))
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+  TurnLeft,
+  TurnRight,
+  Boost,
+  Confirm,
+  Retry,
+  Pause,
+  MenuUp,
+  MenuDown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+  Key(KeyCode),
+  Button(GamepadButtonType),
+  /// pressed when the axis value is at least the threshold
+  AxisAbove(GamepadAxisType, f32),
+  /// pressed when the axis value is at most the threshold
+  AxisBelow(GamepadAxisType, f32),
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct InputBindings {
+  pub actions: HashMap<Action, Vec<Binding>>,
+  /// analog steering, overrides TurnLeft/TurnRight while the stick is moved
+  pub steering_axis: Option<GamepadAxisType>,
+}
+
+impl Default for InputBindings {
+  fn default() -> Self {
+    use Binding::*;
+    Self {
+      actions: HashMap::from([
+        (
+          Action::TurnLeft,
+          vec![
+            Key(KeyCode::A),
+            Key(KeyCode::Left),
+            Button(GamepadButtonType::DPadLeft),
+          ],
+        ),
+        (
+          Action::TurnRight,
+          vec![
+            Key(KeyCode::D),
+            Key(KeyCode::Right),
+            Button(GamepadButtonType::DPadRight),
+          ],
+        ),
+        (
+          Action::Boost,
+          vec![
+            Key(KeyCode::Space),
+            Button(GamepadButtonType::South),
+            AxisAbove(GamepadAxisType::RightZ, 0.5),
+          ],
+        ),
+        (
+          Action::Confirm,
+          vec![
+            Key(KeyCode::Space),
+            Key(KeyCode::Return),
+            Button(GamepadButtonType::South),
+          ],
+        ),
+        (
+          Action::Retry,
+          vec![
+            Key(KeyCode::Space),
+            Key(KeyCode::R),
+            Button(GamepadButtonType::South),
+          ],
+        ),
+        (
+          Action::Pause,
+          vec![Key(KeyCode::Escape), Button(GamepadButtonType::Start)],
+        ),
+        (
+          Action::MenuUp,
+          vec![
+            Key(KeyCode::W),
+            Key(KeyCode::Up),
+            Button(GamepadButtonType::DPadUp),
+          ],
+        ),
+        (
+          Action::MenuDown,
+          vec![
+            Key(KeyCode::S),
+            Key(KeyCode::Down),
+            Button(GamepadButtonType::DPadDown),
+          ],
+        ),
+      ]),
+      steering_axis: Some(GamepadAxisType::LeftStickX),
+    }
+  }
+}
+
+impl InputBindings {
+  /// Bindings from the config dir on native or `localStorage` on wasm, defaults if they can't
+  /// be read. missing bindings are written with the defaults so players have something to edit
+  pub fn load() -> Self {
+    let contents = match read() {
+      Ok(Some(contents)) => contents,
+      Ok(None) => {
+        let bindings = Self::default();
+        bindings.save();
+        return bindings;
+      }
+      Err(e) => {
+        error!("could not read the bindings, using default bindings: {e}");
+        return default();
+      }
+    };
+    let mut bindings: Self = ron::from_str(&contents).unwrap_or_else(|e| {
+      error!("invalid bindings, using default bindings: {e}");
+      default()
+    });
+    // actions added after the file was written keep their default bindings
+    for (action, defaults) in Self::default().actions {
+      bindings.actions.entry(action).or_insert(defaults);
+    }
+    bindings
+  }
+
+  pub fn save(&self) {
+    let result = ron::ser::to_string_pretty(self, default())
+      .map_err(|e| e.to_string())
+      .and_then(|s| write(&s));
+    if let Err(e) = result {
+      error!("failed to save the bindings: {e}");
+    }
+  }
+
+  /// Name of the first key bound to `action` for on-screen prompts,
+  /// the first gamepad binding if there is no key
+  pub fn key_name(&self, action: Action) -> String {
+    let bindings = self.actions.get(&action).map_or(&[][..], Vec::as_slice);
+    let name = bindings
+      .iter()
+      .find(|b| matches!(b, Binding::Key(_)))
+      .or(bindings.first())
+      .map(|b| match b {
+        Binding::Key(key) => format!("{key:?}"),
+        Binding::Button(button) => format!("{button:?}"),
+        Binding::AxisAbove(axis, _) | Binding::AxisBelow(axis, _) => format!("{axis:?}"),
+      });
+    name.map_or_else(|| "(unbound)".to_owned(), |name| name.to_lowercase())
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn bindings_path() -> Result<std::path::PathBuf, String> {
+  dirs::config_dir()
+    .map(|dir| dir.join(CONFIG_DIR).join(BINDINGS_FILE))
+    .ok_or_else(|| "no config directory on this platform".to_owned())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read() -> Result<Option<String>, String> {
+  let path = bindings_path()?;
+  match std::fs::read_to_string(&path) {
+    Ok(contents) => Ok(Some(contents)),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(format!("{}: {e}", path.display())),
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(contents: &str) -> Result<(), String> {
+  let path = bindings_path()?;
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+  }
+  std::fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage() -> Result<web_sys::Storage, String> {
+  web_sys::window()
+    .and_then(|w| w.local_storage().ok().flatten())
+    .ok_or_else(|| "local storage is not available".to_owned())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read() -> Result<Option<String>, String> {
+  storage()?
+    .get_item(BINDINGS_KEY)
+    .map_err(|_| "could not read local storage".to_owned())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(contents: &str) -> Result<(), String> {
+  storage()?
+    .set_item(BINDINGS_KEY, contents)
+    .map_err(|_| "could not write local storage".to_owned())
+}
+
+/// Actions resolved from the current keyboard and gamepad state
+#[derive(Resource, Default)]
+pub struct ActionState {
+  pressed: HashSet<Action>,
+  just_pressed: HashSet<Action>,
+  /// -1.0 (right) to 1.0 (left)
+  pub turn: f32,
+}
+
+impl ActionState {
+  pub fn pressed(&self, action: Action) -> bool {
+    self.pressed.contains(&action)
+  }
+
+  pub fn just_pressed(&self, action: Action) -> bool {
+    self.just_pressed.contains(&action)
+  }
+}
+
+fn update_action_state(
+  bindings: Res<InputBindings>,
+  keys: Res<Input<KeyCode>>,
+  buttons: Res<Input<GamepadButton>>,
+  axes: Res<Axis<GamepadAxis>>,
+  gamepads: Res<Gamepads>,
+  mut state: ResMut<ActionState>,
+) {
+  let axis = |axis_type| {
+    gamepads
+      .iter()
+      .filter_map(|gamepad| axes.get(GamepadAxis::new(gamepad, axis_type)))
+      .find(|v| *v != 0.0)
+  };
+  let is_pressed = |binding: &Binding| match *binding {
+    Binding::Key(key) => keys.pressed(key),
+    Binding::Button(button) => gamepads
+      .iter()
+      .any(|gamepad| buttons.pressed(GamepadButton::new(gamepad, button))),
+    Binding::AxisAbove(axis_type, threshold) => axis(axis_type).is_some_and(|v| v >= threshold),
+    Binding::AxisBelow(axis_type, threshold) => axis(axis_type).is_some_and(|v| v <= threshold),
+  };
+
+  let pressed: HashSet<Action> = bindings
+    .actions
+    .iter()
+    .filter(|(_, b)| b.iter().any(&is_pressed))
+    .map(|(action, _)| *action)
+    .collect();
+  state.just_pressed = pressed.difference(&state.pressed).copied().collect();
+  state.pressed = pressed;
+
+  // analog stick wins over digital turning, stick right is positive but turning right is negative
+  state.turn = match bindings.steering_axis.and_then(&axis) {
+    Some(v) => -v,
+    None => {
+      let left = if state.pressed(Action::TurnLeft) { 1.0 } else { 0.0 };
+      let right = if state.pressed(Action::TurnRight) { 1.0 } else { 0.0 };
+      left - right
+    }
+  };
+}
@@ -1,12 +1,15 @@
 use bevy::prelude::*;
 use bevy_hanabi::HanabiPlugin;
 use game::GameExtensions;
+use input::InputExtensions;
 use jam4::Jam4Extensions;
 use jukebox::JukeboxExtensions;
 use splash::SplashExtensions;
 
-#[cfg(feature = "debug")]
+#[cfg(any(feature = "debug", feature = "editor"))]
 use bevy_egui::EguiPlugin;
+#[cfg(feature = "editor")]
+use editor::EditorExtensions;
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
@@ -15,9 +18,14 @@ enum AppState {
   #[default]
   Splash,
   Game,
+  #[cfg(feature = "editor")]
+  Editor,
 }
 
+#[cfg(feature = "editor")]
+mod editor;
 mod game;
+mod input;
 mod jukebox;
 mod splash;
 
@@ -47,13 +55,19 @@ fn main() {
     .add_plugins(HanabiPlugin)
     .add_state::<AppState>()
     .add_splash_screen(AppState::Splash, AppState::Game)
+    .add_input_actions()
     .add_jukebox()
     .add_jam_game()
-    .add_game(AppState::Game);
+    .add_game(AppState::Game, AppState::Splash);
+
+  #[cfg(any(feature = "debug", feature = "editor"))]
+  app.add_plugins(EguiPlugin);
+
+  #[cfg(feature = "editor")]
+  app.add_level_editor(AppState::Editor, AppState::Splash, AppState::Game);
 
   #[cfg(feature = "debug")]
   app.add_plugins((
-    EguiPlugin,
     utils::fps::ScreenDiagsTextPlugin,
     WorldInspectorPlugin::default(),
   ));
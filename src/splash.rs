@@ -1,5 +1,5 @@
 use bevy::{
-  asset::LoadedFolder,
+  asset::{LoadState, LoadedFolder},
   core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
   prelude::*,
   render::{
@@ -12,12 +12,19 @@ use bevy::{
   },
   sprite::{Material2d, Material2dKey, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
 };
-use jam4::{GameControlCommand, ModManager, SimulationState};
+use jam4::{
+  level::{LevelId, LevelManager, LevelRegistry},
+  save::SaveData,
+  GameControlCommand, ModAsset, ModManager, SimulationState, MODS_FOLDER,
+};
 use utils::{despawn_screen, text::TextAnimation};
 
 use utils::colors::*;
 
-use crate::jukebox::{BgMusic, MusicCommand};
+use crate::{
+  input::{Action, ActionState, InputBindings},
+  jukebox::{BgMusic, MusicCommand},
+};
 
 pub trait SplashExtensions {
   fn add_splash_screen<T: States + Copy>(&mut self, show_on_state: T, next_state: T) -> &mut Self;
@@ -36,13 +43,20 @@ impl SplashExtensions for App {
       )
       .add_systems(
         Update,
-        (wait_for_preload_assets, show_logs, go_to_next_state::<T>).run_if(in_state(show_on_state)),
+        (
+          wait_for_preload_assets,
+          register_mods,
+          show_logs,
+          (select_level, go_to_next_state::<T>).chain(),
+          // show the level select as soon as simulation is ready
+          // also covers coming back to the title after quitting a game
+          on_game_init.run_if(in_state(SimulationState::Ready)),
+        )
+          .run_if(in_state(show_on_state)),
       )
-      .add_systems(OnExit(show_on_state), despawn_screen::<OnSplashScreen>)
-      // skip menus, create a new game as soon as simulation is ready
       .add_systems(
-        OnEnter(SimulationState::Ready),
-        on_game_init.run_if(in_state(show_on_state)),
+        OnExit(show_on_state),
+        (despawn_screen::<OnSplashScreen>, reset_level_list),
       )
   }
 }
@@ -54,7 +68,17 @@ struct OnSplashScreen;
 struct LogText;
 
 #[derive(Component)]
-struct PressSpace;
+struct PressToPlay;
+
+#[derive(Component)]
+struct LevelList;
+
+#[derive(Component)]
+struct LevelListItem {
+  index: usize,
+  id: LevelId,
+  unlocked: bool,
+}
 
 #[derive(Resource)]
 struct SplashNextState<T>(T);
@@ -62,8 +86,11 @@ struct SplashNextState<T>(T);
 #[derive(Resource, Default)]
 struct SplashState {
   pub loaded_handles: Option<Handle<LoadedFolder>>,
+  pub mod_handles: Option<Handle<LoadedFolder>>,
   pub preload_complete: bool,
   pub game_initialized: bool,
+  /// index into the level list
+  pub selected: usize,
 }
 
 #[derive(Event)]
@@ -159,7 +186,19 @@ fn splash_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
           }),
         )
-        .insert(PressSpace);
+        .insert(PressToPlay);
+      parent
+        .spawn(NodeBundle {
+          style: Style {
+            align_items: AlignItems::Center,
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            margin: UiRect::top(Val::Px(20.0)),
+            ..default()
+          },
+          ..default()
+        })
+        .insert(LevelList);
     });
 }
 
@@ -168,17 +207,104 @@ fn preload_assets(
   mut state: ResMut<SplashState>,
   asset_server: Res<AssetServer>,
 ) {
+  if state.preload_complete {
+    return;
+  }
   state.loaded_handles = Some(asset_server.load_folder("preload"));
   log.send("Preloading assets...".into());
 }
 
 fn init_game(
   mut log: EventWriter<SplashLog>,
+  mut state: ResMut<SplashState>,
+  asset_server: Res<AssetServer>,
+  sim_state: Res<State<SimulationState>>,
+  mut save: ResMut<SaveData>,
+  mut lvl_mgr: ResMut<LevelManager>,
+) {
+  // modules are only initialized once
+  if *sim_state.get() != SimulationState::Disabled {
+    return;
+  }
+  match SaveData::load() {
+    Ok(loaded) => {
+      lvl_mgr.unlocked.extend(loaded.unlocked.iter().cloned());
+      *save = loaded;
+      log.send("Loading save data...ok".into());
+    }
+    Err(e) => {
+      error!("failed to load save data: {e}");
+      log.send("Loading save data...failed".into());
+    }
+  }
+  state.mod_handles = Some(asset_server.load_folder(MODS_FOLDER));
+  log.send("Loading mods...".into());
+}
+
+/// registers the base game and every mod found in the mods folder, then initializes them
+fn register_mods(
+  mut log: EventWriter<SplashLog>,
+  mut state: ResMut<SplashState>,
   mut mod_mgr: ResMut<ModManager>,
   mut cmds: EventWriter<GameControlCommand>,
+  asset_server: Res<AssetServer>,
+  folders: Res<Assets<LoadedFolder>>,
+  mods: Res<Assets<ModAsset>>,
 ) {
-  // hard code the base game
+  let Some(handle) = &state.mod_handles else {
+    return;
+  };
+  let mut found: Vec<ModAsset> = match asset_server.load_state(handle) {
+    LoadState::Loaded => folders
+      .get(handle)
+      .map(|folder| {
+        folder
+          .handles
+          .iter()
+          .filter_map(|h| h.clone().try_typed::<ModAsset>().ok())
+          .filter_map(|h| mods.get(&h).cloned())
+          .collect()
+      })
+      .unwrap_or_default(),
+    // no mods folder, or it cannot be listed (wasm)
+    LoadState::Failed => {
+      log.send("Loading mods...none found".into());
+      Vec::new()
+    }
+    _ => return,
+  };
+  state.mod_handles = None;
+  if !found.is_empty() {
+    log.send("Loading mods...ok".into());
+  }
+  found.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+
   mod_mgr.clear().register(base_game::get_module());
+  for m in found {
+    match m.descriptor() {
+      Ok(module) => {
+        mod_mgr.register(module);
+      }
+      Err(e) => {
+        error!("failed to load mod {}: {e}", m.manifest.name);
+        log.send(SplashLog(format!(
+          "Loading mod {}...failed: {e}",
+          m.manifest.name
+        )));
+      }
+    }
+  }
+  let (order, errors) = mod_mgr.resolve();
+  for module in order.iter().filter(|m| m.info.id != base_game::MODULE_ID) {
+    log.send(SplashLog(format!(
+      "Loading mod {} {}...ok",
+      module.info.id, module.info.version
+    )));
+  }
+  for (id, e) in errors {
+    log.send(SplashLog(format!("Loading mod {id}...failed: {e}")));
+  }
+
   // initialize modules
   cmds.send(GameControlCommand::Initialize);
   log.send("Initializing game modules...".into());
@@ -187,16 +313,85 @@ fn init_game(
 fn on_game_init(
   mut log: EventWriter<SplashLog>,
   mut splash_state: ResMut<SplashState>,
-  qry: Query<Entity, With<PressSpace>>,
+  qry: Query<Entity, With<PressToPlay>>,
+  qry_list: Query<Entity, With<LevelList>>,
+  lvl_reg: Res<LevelRegistry>,
+  lvl_mgr: Res<LevelManager>,
+  bindings: Res<InputBindings>,
   mut cmd: Commands,
 ) {
+  if splash_state.game_initialized {
+    return;
+  }
   splash_state.game_initialized = true;
+  splash_state.selected = 0;
   log.send("Initializing game modules...ok".into());
 
   cmd.entity(qry.single()).insert(TextAnimation {
-    text: "Press space to play".to_owned(),
+    text: format!(
+      "Choose a level, press {} to play",
+      bindings.key_name(Action::Confirm)
+    ),
     animation_speed: 1.0,
   });
+
+  cmd.entity(qry_list.single()).with_children(|parent| {
+    for (index, id) in lvl_reg.ordered_levels().into_iter().enumerate() {
+      let Ok(lvl) = lvl_reg.get_level(&id) else {
+        continue;
+      };
+      let unlocked = lvl_mgr.is_unlocked(&id, &lvl_reg);
+      let name = &lvl.name;
+      parent
+        .spawn(TextBundle::from_section(
+          if unlocked {
+            name.clone()
+          } else {
+            format!("{name} (locked)")
+          },
+          TextStyle {
+            font_size: 24.0,
+            color: MISTY,
+            ..default()
+          },
+        ))
+        .insert(LevelListItem {
+          index,
+          id,
+          unlocked,
+        });
+    }
+  });
+}
+
+fn select_level(
+  actions: Res<ActionState>,
+  mut splash_state: ResMut<SplashState>,
+  mut qry: Query<(&mut Text, &LevelListItem)>,
+) {
+  let count = qry.iter().count();
+  if count == 0 {
+    return;
+  }
+  if actions.just_pressed(Action::MenuUp) {
+    splash_state.selected = (splash_state.selected + count - 1) % count;
+  }
+  if actions.just_pressed(Action::MenuDown) {
+    splash_state.selected = (splash_state.selected + 1) % count;
+  }
+
+  for (mut text, item) in qry.iter_mut() {
+    let color = if item.index == splash_state.selected {
+      FAIRY
+    } else if item.unlocked {
+      MISTY
+    } else {
+      LILAC
+    };
+    for section in text.sections.iter_mut() {
+      section.style.color = color;
+    }
+  }
 }
 
 fn show_logs(mut log: EventReader<SplashLog>, mut qry: Query<&mut Text, With<LogText>>) {
@@ -251,16 +446,29 @@ fn go_to_next_state<T: States>(
   mut app_state: ResMut<NextState<T>>,
   splash_state: Res<SplashState>,
   next_state: Res<SplashNextState<T>>,
-  keyboard_input: Res<Input<KeyCode>>,
+  actions: Res<ActionState>,
   mut cmds: EventWriter<GameControlCommand>,
+  qry: Query<&LevelListItem>,
 ) {
-  if keyboard_input.just_pressed(KeyCode::Space)
-    && splash_state.preload_complete
-    && splash_state.game_initialized
+  if !actions.just_pressed(Action::Confirm)
+    || !splash_state.preload_complete
+    || !splash_state.game_initialized
   {
-    cmds.send(GameControlCommand::StartGame);
-    app_state.set(next_state.0.clone());
+    return;
   }
+  let Some(item) = qry.iter().find(|item| item.index == splash_state.selected) else {
+    return;
+  };
+  if !item.unlocked {
+    return;
+  }
+  cmds.send(GameControlCommand::StartLevel(item.id.clone()));
+  app_state.set(next_state.0.clone());
+}
+
+/// the level list is rebuilt when coming back, it may have changed
+fn reset_level_list(mut splash_state: ResMut<SplashState>) {
+  splash_state.game_initialized = false;
 }
 
 pub fn build_bg(